@@ -0,0 +1,117 @@
+//! Moving completed todos out of a list, like todo.txt's `done.txt`.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::archive::ArchiveOptions;
+//!
+//! let mut todo = TodoList::parse("x Old task\nNew task\n");
+//! let mut done = TodoList::new();
+//!
+//! assert_eq!(todo.archive(&mut done, ArchiveOptions::new()), 1);
+//! assert_eq!(todo.to_string(), "New task\n");
+//! assert_eq!(done.to_string(), "x Old task\n");
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+use crate::{Todo, TodoList};
+
+/// Options for archiving completed todos.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    /// Keep completed todos for this many days after their completion date.
+    ///
+    /// Completed todos without a completion date are always archived.
+    pub keep_days: Option<u32>,
+    /// Append archived todos in their original order. Otherwise, they're
+    /// sorted by completion date, oldest first, followed by those without
+    /// one.
+    pub preserve_order: bool,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            keep_days: None,
+            preserve_order: true,
+        }
+    }
+}
+
+impl ArchiveOptions {
+    /// Returns the default options: archive every completed todo, in order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keep_days(mut self, days: Option<u32>) -> Self {
+        self.keep_days = days;
+        self
+    }
+
+    pub fn preserve_order(mut self, preserve: bool) -> Self {
+        self.preserve_order = preserve;
+        self
+    }
+
+    /// Returns true if the todo should be archived at `now`.
+    pub fn should_archive(&self, todo: &Todo, now: NaiveDateTime) -> bool {
+        if !todo.completed {
+            return false;
+        }
+
+        match (self.keep_days, todo.completion_date) {
+            (Some(days), Some(date)) => (now.date() - date.date()).num_days() >= days as i64,
+            _ => true,
+        }
+    }
+
+    /// Removes the todos to archive from `todos`, returning them in the
+    /// order they should be appended.
+    pub(crate) fn take(&self, todos: &mut Vec<Todo>, now: NaiveDateTime) -> Vec<Todo> {
+        let (mut archived, kept) = std::mem::take(todos)
+            .into_iter()
+            .partition(|todo: &Todo| self.should_archive(todo, now));
+        *todos = kept;
+
+        self.order(&mut archived);
+        archived
+    }
+
+    /// Sorts archived todos unless `preserve_order` is set.
+    pub(crate) fn order(&self, archived: &mut [Todo]) {
+        if !self.preserve_order {
+            archived.sort_by_key(|todo| (todo.completion_date.is_none(), todo.completion_date));
+        }
+    }
+}
+
+/// Archives the completed todos of a todo.txt file into a done file.
+///
+/// The done file is created if it doesn't exist, and the archived todos are
+/// appended to it. Returns how many todos were archived.
+pub fn archive_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    todo_path: P,
+    done_path: Q,
+    options: ArchiveOptions,
+) -> io::Result<usize> {
+    let mut todo = TodoList::load(todo_path.as_ref())?;
+    let mut done = match TodoList::load(done_path.as_ref()) {
+        Ok(done) => done,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => TodoList::new(),
+        Err(e) => return Err(e),
+    };
+
+    let count = todo.archive(&mut done, options);
+    if count > 0 {
+        // Save the done file first, so that nothing is lost on failure.
+        done.save(done_path)?;
+        todo.save(todo_path)?;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,354 @@
+//! Kanban boards for the terminal.
+//!
+//! `Board` is a `Renderer` which lays out the columns of a table side by
+//! side, colored like `Todo::colored`:
+//!
+//! ```text
+//! === Todos ===
+//! Work                  | Home
+//! ----------------------+----------------------
+//! (A) Review documents  | Clean desk
+//!   Print them          |
+//! ```
+//!
+//! The board fills the width of the terminal, or 80 characters when there
+//! isn't one, unless given a width. Todos which don't fit in their column
+//! are word-wrapped, or truncated with an ellipsis. Widths are measured in
+//! terminal cells, so wide characters such as CJK and emoji line up.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::board::{Board, Overflow};
+//! use todo_lib::colors::StyleScheme;
+//!
+//! let mut table = TodoTable::new(Some("Chores"));
+//! table.add_col("Now");
+//! table.add_col("Later");
+//! table.add_todo(Todo::from_str("Water the plants").unwrap(), "Now");
+//! table.add_todo(Todo::from_str("Paint the fence +house").unwrap(), "Later");
+//!
+//! let board = Board::new(StyleScheme::default()).width(29);
+//! let lines = [
+//!     "=== Chores ===",
+//!     "Now           | Later",
+//!     "--------------+--------------",
+//!     "Water the     | Paint the",
+//!     "  plants      |   fence",
+//!     "              |   +house",
+//! ];
+//! assert_eq!(table.render(&board), lines.join("\n") + "\n");
+//!
+//! let board = board.overflow(Overflow::Truncate);
+//! assert!(table.render(&board).ends_with("Water the pl… | Paint the fe…\n"));
+//! ```
+
+use terminal_size::{terminal_size, Width};
+use unicode_width::UnicodeWidthChar;
+
+use crate::colors::{StyleScheme, RESET};
+use crate::render::{Ansi, Element, Renderer};
+use crate::{Todo, TodoColumn, TodoTable};
+
+/// The width of a board when there's no terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The text between two columns.
+const SEPARATOR: &str = " | ";
+
+/// What to do with todos wider than their column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wraps at spaces, indenting the following lines.
+    #[default]
+    Wrap,
+    /// Cuts the todo short, ending with `…`.
+    Truncate,
+}
+
+/// Renders tables as side-by-side columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Board<'a> {
+    pub style: StyleScheme<'a>,
+    /// The width of the board, or `None` to fill the terminal.
+    pub width: Option<usize>,
+    pub overflow: Overflow,
+}
+
+impl<'a> Board<'a> {
+    pub fn new(style: StyleScheme<'a>) -> Self {
+        Self {
+            style,
+            width: None,
+            overflow: Overflow::Wrap,
+        }
+    }
+
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Returns the width of the board, checking the terminal and then the
+    /// `COLUMNS` environment variable if it wasn't given.
+    fn total_width(&self) -> usize {
+        self.width
+            .or_else(|| terminal_size().map(|(Width(w), _)| w as usize))
+            .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH)
+    }
+
+    /// Fits a line of a column into lines of the given width.
+    fn fit(&self, line: &str, width: usize) -> Vec<String> {
+        let text = line.trim_start_matches(' ');
+        // Deeply nested subtasks still get a character of the line.
+        let indent = (line.len() - text.len()).min(width.saturating_sub(1));
+
+        match self.overflow {
+            Overflow::Truncate => {
+                let text = truncate(text, width.saturating_sub(indent));
+                vec![format!("{}{text}", " ".repeat(indent))]
+            }
+            Overflow::Wrap => {
+                // Continuation lines hang below the first, if there's room.
+                let hang = (indent + 2).min(width.saturating_sub(1));
+                wrap(text, width.saturating_sub(indent), width - hang)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, text)| {
+                        let indent = if i == 0 { indent } else { hang };
+                        format!("{}{text}", " ".repeat(indent))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Renderer for Board<'_> {
+    fn element(&self, todo: &Todo, element: Element, text: &str) -> String {
+        Ansi::new(self.style).element(todo, element, text)
+    }
+
+    fn todo(&self, todo: &Todo, elements: Vec<(Element, String)>) -> String {
+        Ansi::new(self.style).todo(todo, elements)
+    }
+
+    /// Renders the title and todos of a column as lines, for `table`.
+    fn column(&self, col: &TodoColumn, todos: Vec<(usize, String)>) -> String {
+        let mut s = col.title.clone();
+        for (depth, todo) in todos {
+            s.push_str(&format!("\n{}{todo}", "  ".repeat(depth)));
+        }
+
+        s
+    }
+
+    fn table(&self, table: &TodoTable, columns: Vec<String>) -> String {
+        let mut s = format!("=== {} ===\n", table.title());
+        if columns.is_empty() {
+            return s;
+        }
+
+        let gaps = SEPARATOR.len() * (columns.len() - 1);
+        let width = (self.total_width().saturating_sub(gaps) / columns.len()).max(1);
+
+        let mut titles = Vec::new();
+        let mut cells = Vec::new();
+        for col in &columns {
+            let mut lines = col.split('\n');
+            titles.push(truncate(lines.next().unwrap_or_default(), width));
+            cells.push(
+                lines
+                    .flat_map(|line| self.fit(line, width))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        s.push_str(&row(&titles, width));
+        s.push_str(&vec!["-".repeat(width); columns.len()].join("-+-"));
+        s.push('\n');
+
+        let height = cells.iter().map(Vec::len).max().unwrap_or_default();
+        for i in 0..height {
+            let line: Vec<String> = cells
+                .iter()
+                .map(|lines| lines.get(i).cloned().unwrap_or_default())
+                .collect();
+            s.push_str(&row(&line, width));
+        }
+
+        s
+    }
+}
+
+/// Returns a line of cells, padded to the width and separated, without
+/// trailing spaces.
+fn row(cells: &[String], width: usize) -> String {
+    let mut line = String::new();
+    for (i, cell) in cells.iter().enumerate() {
+        if i != 0 {
+            line.push_str(SEPARATOR);
+        }
+
+        line.push_str(cell);
+        if i != cells.len() - 1 {
+            line.push_str(&" ".repeat(width.saturating_sub(visible_width(cell))));
+        }
+    }
+
+    format!("{}\n", line.trim_end_matches(' '))
+}
+
+/// A piece of styled text.
+enum Token<'a> {
+    /// An escape code, taking no room.
+    Escape(&'a str),
+    Char(char, usize),
+}
+
+/// Splits text into characters and escape codes.
+fn tokens(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+
+    while let Some(ch) = rest.chars().next() {
+        let len = if ch == '\x1b' {
+            // An SGR code ends with the first letter after `[`.
+            let end = rest[1..]
+                .find(|c: char| c.is_ascii_alphabetic())
+                .map_or(rest.len(), |i| i + 2);
+            tokens.push(Token::Escape(&rest[..end]));
+            end
+        } else {
+            tokens.push(Token::Char(ch, ch.width().unwrap_or(0)));
+            ch.len_utf8()
+        };
+
+        rest = &rest[len..];
+    }
+
+    tokens
+}
+
+/// Returns how many terminal cells text takes up, ignoring escape codes.
+fn visible_width(s: &str) -> usize {
+    tokens(s)
+        .into_iter()
+        .map(|token| match token {
+            Token::Escape(_) => 0,
+            Token::Char(_, width) => width,
+        })
+        .sum()
+}
+
+/// Builds lines of styled text, carrying the active style across lines.
+#[derive(Default)]
+struct Lines {
+    lines: Vec<String>,
+    line: String,
+    width: usize,
+    /// The escape codes since the last reset.
+    active: String,
+}
+
+impl Lines {
+    fn push(&mut self, token: &Token) {
+        match *token {
+            Token::Escape(code) => {
+                self.line.push_str(code);
+                if code == RESET {
+                    self.active.clear();
+                } else {
+                    self.active.push_str(code);
+                }
+            }
+            Token::Char(ch, width) => {
+                self.line.push(ch);
+                self.width += width;
+            }
+        }
+    }
+
+    /// Ends the current line, and starts the next in the active style.
+    fn break_line(&mut self) {
+        let mut line = std::mem::replace(&mut self.line, self.active.clone());
+        if !self.active.is_empty() {
+            line.push_str(RESET);
+        }
+
+        self.lines.push(line);
+        self.width = 0;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        self.lines.push(self.line);
+        self.lines
+    }
+}
+
+/// Word-wraps styled text, to a width for the first line and another for
+/// the following lines. Words too long for a line are broken up.
+fn wrap(s: &str, first: usize, rest: usize) -> Vec<String> {
+    let mut lines = Lines::default();
+    let limit = |lines: &Lines| if lines.lines.is_empty() { first } else { rest };
+
+    for (i, word) in s.split(' ').enumerate() {
+        let word = tokens(word);
+        let width: usize = word
+            .iter()
+            .map(|token| match token {
+                Token::Escape(_) => 0,
+                Token::Char(_, width) => *width,
+            })
+            .sum();
+
+        if i != 0 {
+            if lines.width + 1 + width <= limit(&lines) {
+                lines.push(&Token::Char(' ', 1));
+            } else {
+                lines.break_line();
+            }
+        }
+
+        for token in &word {
+            if let Token::Char(_, width) = token {
+                if lines.width > 0 && lines.width + width > limit(&lines) {
+                    lines.break_line();
+                }
+            }
+            lines.push(token);
+        }
+    }
+
+    lines.finish()
+}
+
+/// Cuts styled text short to fit a width, ending it with `…` if it doesn't.
+fn truncate(s: &str, width: usize) -> String {
+    if visible_width(s) <= width {
+        return s.to_string();
+    }
+
+    let mut lines = Lines::default();
+    for token in tokens(s) {
+        match token {
+            Token::Char(_, w) if lines.width + w + 1 > width => break,
+            token => lines.push(&token),
+        }
+    }
+
+    if width > 0 {
+        lines.push(&Token::Char('…', 1));
+    }
+    if !lines.active.is_empty() {
+        lines.line.push_str(RESET);
+    }
+
+    lines.line
+}
@@ -0,0 +1,75 @@
+//! Sources of the current time.
+//!
+//! Everything which depends on the current time has an `_at` variant taking
+//! the time instead, such as `Todo::complete_at` and `IsDue::due_at`; the
+//! plain versions use `SystemClock`. Passing times from a `Clock` lets
+//! callers swap the system clock for a `FixedClock` in tests, or preview
+//! what's due next week.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::chrono::{Duration, NaiveDate};
+//! use todo_lib::clock::{Clock, FixedClock};
+//!
+//! let start = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap().and_hms_opt(9, 0, 0).unwrap();
+//! let mut clock = FixedClock::new(start);
+//!
+//! let todo = Todo::from_str_at("Renew passport due:fri", clock.now()).unwrap();
+//! assert!(!todo.due_at(clock.now()));
+//!
+//! clock.advance(Duration::weeks(1));
+//! assert!(todo.due_at(clock.now()));
+//! ```
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+
+/// A source of the current local time.
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> NaiveDateTime {
+        (**self).now()
+    }
+}
+
+/// The system's local time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// A clock which only moves when told to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub NaiveDateTime);
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self(now)
+    }
+
+    pub fn set(&mut self, now: NaiveDateTime) {
+        self.0 = now;
+    }
+
+    /// Moves the clock forward, or backward for negative deltas.
+    pub fn advance(&mut self, delta: Duration) {
+        self.0 += delta;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.0
+    }
+}
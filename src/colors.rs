@@ -1,3 +1,5 @@
+use std::io::IsTerminal;
+
 use const_format::formatcp;
 
 pub const RED: &str = "\x1b[38;5;1m";
@@ -31,6 +33,47 @@ pub const DEFAULT_STYLE: StyleScheme<'_> = StyleScheme {
     metadata: formatcp!("{ITALIC}{DBLUE}"),
 };
 
+/// When to use colors; see `StyleScheme::with_color`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorMode {
+    #[default]
+    Always,
+    Never,
+    /// Uses colors if stdout is a terminal, unless the `NO_COLOR`
+    /// environment variable is set. `CLICOLOR_FORCE` forces colors either
+    /// way.
+    Auto,
+}
+
+impl ColorMode {
+    /// Returns whether to use colors, checking the environment and stdout
+    /// for `Auto`.
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => auto_enabled(
+                std::env::var("NO_COLOR").ok().as_deref(),
+                std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+                std::io::stdout().is_terminal(),
+            ),
+        }
+    }
+}
+
+/// Decides `ColorMode::Auto` from `NO_COLOR`, `CLICOLOR_FORCE` and whether
+/// stdout is a terminal. Empty variables count as unset, as does
+/// `CLICOLOR_FORCE=0`.
+pub(crate) fn auto_enabled(no_color: Option<&str>, force: Option<&str>, terminal: bool) -> bool {
+    if force.is_some_and(|force| !force.is_empty() && force != "0") {
+        true
+    } else if no_color.is_some_and(|no_color| !no_color.is_empty()) {
+        false
+    } else {
+        terminal
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub struct StyleScheme<'a> {
     pub faded: &'a str,
@@ -103,6 +146,19 @@ impl<'a> StyleScheme<'a> {
         self
     }
 
+    /// Returns the scheme if the color mode allows colors, or else an empty
+    /// scheme, which writes no escape codes.
+    ///
+    /// Schemes are always used as they are otherwise, so colors only turn
+    /// off when asked to.
+    pub fn with_color(self, color: ColorMode) -> Self {
+        if color.enabled() {
+            self
+        } else {
+            Self::new()
+        }
+    }
+
     pub fn get_colors(&self, fade: bool) -> (&'static str, Self) {
         if fade {
             ("", Self::new().faded(Some(self.faded)))
@@ -0,0 +1,403 @@
+//! CSV and TSV, for spreadsheets.
+//!
+//! Each todo is a row, with these columns:
+//!
+//! - `column`: the title of the todo's column
+//! - `completed`: `true` or `false`
+//! - `priority`: a letter, or empty
+//! - `creation`, `completion` and `threshold`: `YYYY-MM-DD` dates, or empty
+//! - `due`: the deadline as written in todo.txt, such as `2024-05-01` or
+//!   `2024-05-01T14:30+02:00`, or empty; `TodoDate::Always` is written as
+//!   `0000-00-00`
+//! - `description`: the description text, without tags
+//! - `projects` and `contexts`: the tags, without `+` or `@`, separated by
+//!   spaces
+//!
+//! followed by a column for each metadata key, in the order they're first
+//! seen. Keys named like one of the above columns are prefixed with `meta:`,
+//! such as `meta:priority`. Fields are quoted as in RFC 4180, whatever the
+//! delimiter.
+//!
+//! Spreadsheets run fields starting with `=`, `+`, `-` or `@` as formulas,
+//! so a description such as `=HYPERLINK(...)` does something when the file
+//! is opened. With `CsvOptions::escape_formulas`, such fields are written
+//! with a leading `'`, which spreadsheets hide, and it's removed again when
+//! reading. Functions taking `CsvOptions` also take a plain delimiter,
+//! without escaping.
+//!
+//! When importing, the columns may be in any order, and any column which
+//! isn't one of the above is a metadata key. Missing columns and empty
+//! fields are left unset, and todos without a column title go in a column
+//! titled `Todos`.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::csv;
+//!
+//! let mut table = TodoTable::new(Some("Backlog"));
+//! table.add_col("Next");
+//! table.add_todo(Todo::from_str("(A) Ship it, now +app @work due:2024-01-31 est:2").unwrap(), "Next");
+//!
+//! let text = csv::table_to_csv(&table, csv::COMMA);
+//! assert_eq!(
+//!     text,
+//!     "column,completed,priority,creation,completion,due,threshold,description,projects,contexts,est\n\
+//!      Next,false,A,,,2024-01-31,,\"Ship it, now\",app,work,2\n",
+//! );
+//!
+//! let parsed = csv::parse_csv(&text, csv::COMMA).unwrap();
+//! assert_eq!(parsed.columns()[0].todos[0].to_string(), "(A) Ship it, now +app @work due:2024-01-31 est:2");
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::error::CsvError;
+use crate::helper::is_meta_value;
+use crate::{
+    Todo, TodoColumn, TodoDate, TodoDescription, TodoPriority, TodoSegment, TodoTable, TodoTag,
+};
+
+/// The CSV delimiter.
+pub const COMMA: char = ',';
+/// The TSV delimiter.
+pub const TAB: char = '\t';
+
+/// The columns every row has, before the metadata columns.
+const FIELDS: [&str; 10] = [
+    "column",
+    "completed",
+    "priority",
+    "creation",
+    "completion",
+    "due",
+    "threshold",
+    "description",
+    "projects",
+    "contexts",
+];
+
+/// How fields are delimited and escaped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Write fields which a spreadsheet would run as formulas, and those
+    /// starting with `'`, with a leading `'`, and remove one leading `'`
+    /// from every field when reading.
+    pub escape_formulas: bool,
+}
+
+impl CsvOptions {
+    /// Returns options for the delimiter, without escaping.
+    pub fn new(delimiter: char) -> Self {
+        Self {
+            delimiter,
+            escape_formulas: false,
+        }
+    }
+
+    pub fn escape_formulas(mut self, escape_formulas: bool) -> Self {
+        self.escape_formulas = escape_formulas;
+        self
+    }
+}
+
+impl From<char> for CsvOptions {
+    fn from(delimiter: char) -> Self {
+        Self::new(delimiter)
+    }
+}
+
+/// The prefix of metadata columns named like one of `FIELDS`.
+const META_PREFIX: &str = "meta:";
+
+/// The title of the column for todos without one.
+const DEFAULT_COLUMN: &str = "Todos";
+
+/// Returns a column as CSV, with a header row.
+pub fn column_to_csv<O: Into<CsvOptions>>(col: &TodoColumn, options: O) -> String {
+    to_csv(std::iter::once(col), options.into())
+}
+
+/// Returns every column of a table as CSV, with a header row.
+pub fn table_to_csv<O: Into<CsvOptions>>(table: &TodoTable, options: O) -> String {
+    to_csv(table.columns(), options.into())
+}
+
+/// Parses CSV with a header row into a table, with a column for each
+/// column title in order of appearance.
+pub fn parse_csv<S: AsRef<str>, O: Into<CsvOptions>>(
+    text: S,
+    options: O,
+) -> Result<TodoTable, CsvError> {
+    let mut records = records(text.as_ref(), options.into())?.into_iter();
+    let (_, header) = records.next().ok_or(CsvError::MissingHeader)?;
+
+    let mut table = TodoTable::new::<String>(None);
+    for (line, record) in records {
+        let mut todo = Todo::default();
+        let mut column = DEFAULT_COLUMN;
+
+        for (name, value) in header.iter().zip(&record) {
+            if value.is_empty() {
+                continue;
+            }
+
+            let bad_value = || CsvError::BadValue {
+                line,
+                column: name.clone(),
+                value: value.clone(),
+            };
+            let date = || parse_date(value).ok_or_else(bad_value);
+
+            match name.as_str() {
+                "column" => column = value.as_str(),
+                "completed" => {
+                    todo.completed = match value.to_lowercase().as_str() {
+                        "true" | "x" | "yes" | "1" => true,
+                        "false" | "no" | "0" => false,
+                        _ => return Err(bad_value()),
+                    }
+                }
+                "priority" => {
+                    todo.priority = TodoPriority::try_from(format!("({value})").as_str())
+                        .map_err(|_| bad_value())?
+                }
+                "creation" => todo.creation = Some(date()?),
+                "completion" => todo.completion_date = Some(date()?),
+                "due" => todo.deadline = TodoDate::parse_value(value).ok_or_else(bad_value)?,
+                "threshold" => todo.threshold = Some(date()?),
+                "description" => {
+                    let description = Todo::new(value, TodoDate::Never, TodoPriority::None);
+                    todo.description.0.splice(0..0, description.description.0);
+                }
+                "projects" | "contexts" => {
+                    for tag in value.split_whitespace() {
+                        let tag = match name.as_str() {
+                            "projects" => TodoTag::project(tag),
+                            _ => TodoTag::context(tag),
+                        };
+                        if let Some(tag) = tag.filter(|tag| !todo.has_tag(tag.clone())) {
+                            todo.description.0.push(TodoSegment::Tag(tag));
+                        }
+                    }
+                }
+                key => {
+                    let key = key
+                        .strip_prefix(META_PREFIX)
+                        .filter(|key| FIELDS.contains(key))
+                        .unwrap_or(key);
+                    if !is_meta_value(key) || !is_meta_value(value) {
+                        return Err(bad_value());
+                    }
+                    todo.set_meta(key, value);
+                }
+            }
+        }
+
+        if table.col(column).is_none() {
+            table.add_col(column);
+        }
+        table.add_todo(todo, column);
+    }
+
+    Ok(table)
+}
+
+/// Reads CSV with a header row into a table.
+///
+/// Parse errors are returned as `io::ErrorKind::InvalidData`.
+pub fn read<R: Read, O: Into<CsvOptions>>(mut reader: R, options: O) -> io::Result<TodoTable> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_csv(text, options).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Reads a CSV file with a header row into a table.
+pub fn load<P: AsRef<Path>, O: Into<CsvOptions>>(path: P, options: O) -> io::Result<TodoTable> {
+    read(File::open(path)?, options)
+}
+
+/// Writes a table as CSV.
+pub fn write<W: Write, O: Into<CsvOptions>>(
+    mut writer: W,
+    table: &TodoTable,
+    options: O,
+) -> io::Result<()> {
+    writer.write_all(table_to_csv(table, options).as_bytes())?;
+    writer.flush()
+}
+
+/// Writes a table as CSV to a file, creating or truncating it.
+pub fn save<P: AsRef<Path>, O: Into<CsvOptions>>(
+    path: P,
+    table: &TodoTable,
+    options: O,
+) -> io::Result<()> {
+    write(BufWriter::new(File::create(path)?), table, options)
+}
+
+/// Returns the todos of the columns as CSV, with a header row.
+fn to_csv<'a, I: IntoIterator<Item = &'a TodoColumn>>(columns: I, options: CsvOptions) -> String {
+    let columns: Vec<_> = columns.into_iter().collect();
+
+    let mut keys: Vec<&String> = Vec::new();
+    for todo in columns.iter().flat_map(|col| col.iter()) {
+        for (key, _) in &todo.metadata.data {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut header: Vec<String> = FIELDS.iter().map(|field| field.to_string()).collect();
+    header.extend(keys.iter().map(|&key| {
+        if FIELDS.contains(&key.as_str()) {
+            format!("{META_PREFIX}{key}")
+        } else {
+            key.clone()
+        }
+    }));
+    let mut s = record(&header, options);
+
+    for col in columns {
+        for todo in col.iter() {
+            let mut text = Vec::new();
+            let mut projects = Vec::new();
+            let mut contexts = Vec::new();
+            for seg in &todo.description.0 {
+                match seg {
+                    TodoSegment::Tag(TodoTag::Project(p)) => projects.push(p.as_str()),
+                    TodoSegment::Tag(TodoTag::Context(c)) => contexts.push(c.as_str()),
+                    seg if !seg.is_inline_meta() => text.push(seg.clone()),
+                    _ => {}
+                }
+            }
+
+            let date = |date: Option<NaiveDateTime>| {
+                date.map(|date| date.format("%F").to_string())
+                    .unwrap_or_default()
+            };
+
+            let mut fields = vec![
+                col.title.clone(),
+                todo.completed.to_string(),
+                if todo.priority.is_some() {
+                    (todo.priority as u8 as char).to_string()
+                } else {
+                    String::new()
+                },
+                date(todo.creation),
+                date(todo.completion_date),
+                todo.deadline.value(),
+                date(todo.threshold),
+                TodoDescription(text).to_string(),
+                projects.join(" "),
+                contexts.join(" "),
+            ];
+            fields.extend(
+                keys.iter()
+                    .map(|&key| todo.metadata.get(key).cloned().unwrap_or_default()),
+            );
+
+            s.push_str(&record(&fields, options));
+        }
+    }
+
+    s
+}
+
+/// Returns a row, escaping and quoting fields as needed.
+fn record<S: AsRef<str>>(fields: &[S], options: CsvOptions) -> String {
+    let delimiter = options.delimiter;
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let mut field = field.as_ref().to_string();
+            if options.escape_formulas && field.starts_with(['=', '+', '-', '@', '\'']) {
+                field.insert(0, '\'');
+            }
+
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+
+    format!("{}\n", fields.join(&delimiter.to_string()))
+}
+
+/// Splits CSV into records of fields, along with the line each starts on.
+/// Blank lines are skipped.
+fn records(text: &str, options: CsvOptions) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let delimiter = options.delimiter;
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' {
+            line += 1;
+        }
+
+        match ch {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            _ if quoted => field.push(ch),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                start = line;
+            }
+            _ if ch == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+
+    if quoted {
+        return Err(CsvError::UnclosedQuote(start));
+    }
+
+    record.push(field);
+    if record.len() > 1 || !record[0].is_empty() {
+        records.push((start, record));
+    }
+
+    if options.escape_formulas {
+        for field in records.iter_mut().flat_map(|(_, record)| record) {
+            if field.starts_with('\'') {
+                field.remove(0);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Parses a `YYYY-MM-DD` date.
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    NaiveDate::parse_from_str(value, "%F")
+        .ok()
+        .map(|date| date.and_time(NaiveTime::MIN))
+}
@@ -0,0 +1,216 @@
+//! Dependencies between todos.
+//!
+//! A todo depends on the todos whose IDs are listed in its `dep:` tag, and
+//! blocks those listed in its `blocks:` tag. Several IDs are separated by
+//! commas, e.g. `dep:3,4`.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::deps::DependencyGraph;
+//!
+//! let mut table = TodoTable::new(Some("Release"));
+//! table.add_col("Todo");
+//! table.add_col("Done");
+//!
+//! table.add_todo(Todo::from_str("Write code id:1 blocks:2").unwrap(), "Done");
+//! table.add_todo(Todo::from_str("Write tests id:2").unwrap(), "Todo");
+//! table.add_todo(Todo::from_str("Publish id:3 dep:1,2").unwrap(), "Todo");
+//! table.get_todo_by_id("1").unwrap().complete();
+//!
+//! let graph = DependencyGraph::from_table(&table);
+//! let ids = |todos: Vec<&Todo>| todos.iter().map(|t| t.id().unwrap().clone()).collect::<Vec<_>>();
+//!
+//! assert_eq!(ids(graph.ready()), ["2"]);
+//! assert_eq!(ids(graph.blocked()), ["3"]);
+//! assert_eq!(ids(graph.topological_order().unwrap()), ["1", "2", "3"]);
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{Todo, TodoTable};
+
+/// A dependency graph over a set of todos.
+#[derive(Clone, Debug)]
+pub struct DependencyGraph<'a> {
+    todos: Vec<&'a Todo>,
+    /// The indices of the todos each todo depends on.
+    deps: Vec<BTreeSet<usize>>,
+    /// Dependencies on IDs which don't exist.
+    missing: Vec<(usize, String)>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    /// Builds the dependency graph of the given todos.
+    ///
+    /// If several todos share an ID, the first one is used.
+    pub fn new<I: IntoIterator<Item = &'a Todo>>(todos: I) -> Self {
+        let todos: Vec<_> = todos.into_iter().collect();
+
+        let mut ids = HashMap::new();
+        for (i, todo) in todos.iter().enumerate() {
+            if let Some(id) = todo.id() {
+                ids.entry(id.as_str()).or_insert(i);
+            }
+        }
+
+        let mut deps = vec![BTreeSet::new(); todos.len()];
+        let mut missing = Vec::new();
+        for (i, todo) in todos.iter().enumerate() {
+            for id in todo.dependencies() {
+                match ids.get(id) {
+                    Some(&j) => {
+                        deps[i].insert(j);
+                    }
+                    None => missing.push((i, id.to_string())),
+                }
+            }
+
+            for id in todo.blocks() {
+                match ids.get(id) {
+                    Some(&j) => {
+                        deps[j].insert(i);
+                    }
+                    None => missing.push((i, id.to_string())),
+                }
+            }
+        }
+
+        Self {
+            todos,
+            deps,
+            missing,
+        }
+    }
+
+    /// Builds the dependency graph of every todo in a table.
+    pub fn from_table(table: &'a TodoTable) -> Self {
+        Self::new(table.iter())
+    }
+
+    /// Returns the todos the given todo directly depends on.
+    pub fn dependencies_of<S: AsRef<str>>(&self, id: S) -> Vec<&'a Todo> {
+        self.index_of(id.as_ref())
+            .map(|i| self.deps[i].iter().map(|&j| self.todos[j]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the todo with the given ID is incomplete and
+    /// depends on an incomplete todo.
+    pub fn is_blocked<S: AsRef<str>>(&self, id: S) -> bool {
+        self.index_of(id.as_ref())
+            .is_some_and(|i| self.blocked_at(i))
+    }
+
+    /// Returns the incomplete todos which depend on an incomplete todo.
+    pub fn blocked(&self) -> Vec<&'a Todo> {
+        (0..self.todos.len())
+            .filter(|&i| self.blocked_at(i))
+            .map(|i| self.todos[i])
+            .collect()
+    }
+
+    /// Returns the incomplete todos whose dependencies are all complete.
+    pub fn ready(&self) -> Vec<&'a Todo> {
+        (0..self.todos.len())
+            .filter(|&i| !self.todos[i].completed && !self.blocked_at(i))
+            .map(|i| self.todos[i])
+            .collect()
+    }
+
+    /// Returns the todos which depend on, or block, an ID that doesn't
+    /// exist, along with that ID. Missing IDs never block a todo.
+    pub fn missing(&self) -> Vec<(&'a Todo, &str)> {
+        self.missing
+            .iter()
+            .map(|(i, id)| (self.todos[*i], id.as_str()))
+            .collect()
+    }
+
+    /// Returns a dependency cycle, if there is one.
+    ///
+    /// Each todo in the returned cycle depends on the next, and the last
+    /// depends on the first.
+    pub fn find_cycle(&self) -> Option<Vec<&'a Todo>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum State {
+            New,
+            Active,
+            Done,
+        }
+
+        let mut state = vec![State::New; self.todos.len()];
+        for start in 0..self.todos.len() {
+            if state[start] != State::New {
+                continue;
+            }
+
+            // Iterative DFS, keeping the current path on the stack.
+            let mut stack = vec![(start, self.deps[start].iter())];
+            state[start] = State::Active;
+
+            while let Some((node, edges)) = stack.last_mut() {
+                let node = *node;
+                match edges.next() {
+                    Some(&next) if state[next] == State::Active => {
+                        let pos = stack.iter().position(|(n, _)| *n == next)?;
+                        return Some(stack[pos..].iter().map(|(n, _)| self.todos[*n]).collect());
+                    }
+                    Some(&next) if state[next] == State::New => {
+                        state[next] = State::Active;
+                        stack.push((next, self.deps[next].iter()));
+                    }
+                    Some(_) => {}
+                    None => {
+                        state[node] = State::Done;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns every todo ordered so that each comes after all of its
+    /// dependencies, keeping the original order where possible.
+    ///
+    /// Returns None if there is a dependency cycle.
+    pub fn topological_order(&self) -> Option<Vec<&'a Todo>> {
+        let mut remaining: Vec<usize> = self.deps.iter().map(|deps| deps.len()).collect();
+        let mut dependents = vec![Vec::new(); self.todos.len()];
+        for (i, deps) in self.deps.iter().enumerate() {
+            for &j in deps {
+                dependents[j].push(i);
+            }
+        }
+
+        let mut available: BTreeSet<usize> = (0..self.todos.len())
+            .filter(|&i| remaining[i] == 0)
+            .collect();
+
+        let mut order = Vec::with_capacity(self.todos.len());
+        while let Some(i) = available.pop_first() {
+            order.push(self.todos[i]);
+
+            for &j in &dependents[i] {
+                remaining[j] -= 1;
+                if remaining[j] == 0 {
+                    available.insert(j);
+                }
+            }
+        }
+
+        (order.len() == self.todos.len()).then_some(order)
+    }
+
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.todos
+            .iter()
+            .position(|todo| todo.id().is_some_and(|i| i == id))
+    }
+
+    fn blocked_at(&self, i: usize) -> bool {
+        !self.todos[i].completed && self.deps[i].iter().any(|&j| !self.todos[j].completed)
+    }
+}
@@ -1,11 +1,21 @@
 use std::fmt::Display;
 
-use chrono::{Datelike, Local, NaiveDateTime};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
+};
 
 use crate::helper::IsDue;
 
 /// A due date for a Todo. Encapsulates data structures from crate `datetime`.
 ///
+/// In todo.txt, deadlines are written as a date, a date and time, or a date
+/// and time with a UTC offset:
+///
+/// - `due:2024-05-01`: due that day, see `DuePolicy`
+/// - `due:2024-05-01T14:30`: due at 14:30 local time
+/// - `due:2024-05-01T14:30+02:00`: due at 14:30 two hours east of UTC, or
+///   12:30 UTC; `Z` stands for UTC, and `+0200` or `+02` work too
+///
 /// Example:
 /// ```
 /// use todo_lib::prelude::*;
@@ -15,25 +25,54 @@ use crate::helper::IsDue;
 ///
 /// let indefinite_deadline = TodoDate::Never;
 /// assert!(!indefinite_deadline.due());
+///
+/// let todo = Todo::from_str("Call Tokyo due:2024-05-01T09:00+09:00").unwrap();
+/// assert!(matches!(todo.deadline, TodoDate::Zoned(_)));
+/// assert_eq!(todo.to_string(), "Call Tokyo due:2024-05-01T09:00+09:00");
 /// ```
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum TodoDate {
     /// Never due.
     #[default]
     Never,
     /// Always due.
     Always,
-    /// Due on a specific day.
+    /// Due on a specific day, whatever the time; see `DuePolicy`.
     Day(NaiveDateTime),
+    /// Due at a specific local time.
+    Timed(NaiveDateTime),
+    /// Due at a specific time in a UTC offset.
+    Zoned(DateTime<FixedOffset>),
+}
+
+/// When during the day a deadline without a time falls due.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DuePolicy {
+    /// Due from the start of the day.
+    #[default]
+    StartOfDay,
+    /// Due once the day is over.
+    EndOfDay,
 }
 
 impl IsDue for TodoDate {
-    /// Returns true if it is currently on or past the due date.
-    fn due(&self) -> bool {
+    /// Returns true if it is on or past the due date.
+    fn due_at(&self, now: NaiveDateTime) -> bool {
+        self.due_with(now, DuePolicy::default())
+    }
+
+    fn due_with(&self, now: NaiveDateTime, policy: DuePolicy) -> bool {
         match self {
             Self::Never => false,
             Self::Always => true,
-            Self::Day(t) => *t <= Local::now().naive_local(),
+            Self::Day(t) => match day_boundary(t.date(), policy) {
+                Some(boundary) => boundary <= now,
+                None => true,
+            },
+            Self::Timed(t) => *t <= now,
+            Self::Zoned(t) => t.with_timezone(&Local).naive_local() <= now,
         }
     }
 }
@@ -42,8 +81,7 @@ impl Display for TodoDate {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Never => write!(f, ""),
-            Self::Always => write!(f, "due:0000-00-00"),
-            Self::Day(t) => write!(f, "due:{}-{:02}-{:02}", t.year(), t.month(), t.day(),),
+            _ => write!(f, "due:{}", self.value()),
         }
     }
 }
@@ -60,4 +98,150 @@ impl TodoDate {
     pub fn is_none(&self) -> bool {
         !self.is_some()
     }
+
+    /// Returns the deadline as written after `due:`, such as `2024-05-01`
+    /// or `2024-05-01T14:30+02:00`, or an empty string for `Never`.
+    pub fn value(&self) -> String {
+        match self {
+            Self::Never => String::new(),
+            Self::Always => "0000-00-00".to_string(),
+            Self::Day(t) => t.format("%F").to_string(),
+            Self::Timed(t) => format_time(*t),
+            Self::Zoned(t) => {
+                let offset = match t.offset().local_minus_utc() {
+                    0 => "Z".to_string(),
+                    _ => t.format("%:z").to_string(),
+                };
+                format_time(t.naive_local()) + &offset
+            }
+        }
+    }
+
+    /// Parses a deadline as written after `due:`.
+    ///
+    /// Relative dates aren't understood; see `RelativeDate` for those.
+    pub fn parse_value<S: AsRef<str>>(s: S) -> Option<Self> {
+        let s = s.as_ref();
+        if s == "0000-00-00" {
+            return Some(Self::Always);
+        }
+
+        match NaiveDate::parse_from_str(s, "%F") {
+            Ok(date) => Some(Self::Day(date.and_time(NaiveTime::MIN))),
+            Err(_) => parse_time(s),
+        }
+    }
+
+    /// Returns the deadline in local time, if it has a date.
+    pub fn local(&self) -> Option<NaiveDateTime> {
+        match self {
+            Self::Never | Self::Always => None,
+            Self::Day(t) | Self::Timed(t) => Some(*t),
+            Self::Zoned(t) => Some(t.with_timezone(&Local).naive_local()),
+        }
+    }
+
+    /// Returns when the deadline falls due, in a timezone.
+    ///
+    /// Deadlines without an offset are taken to be in that timezone. Returns
+    /// `None` for `Never` and `Always`.
+    pub fn due_in<Tz: TimeZone>(&self, tz: &Tz, policy: DuePolicy) -> Option<DateTime<Tz>> {
+        match self {
+            Self::Never | Self::Always => None,
+            Self::Day(t) => in_timezone(tz, day_boundary(t.date(), policy)?),
+            Self::Timed(t) => in_timezone(tz, *t),
+            Self::Zoned(t) => Some(t.with_timezone(tz)),
+        }
+    }
+
+    /// Returns the same kind of deadline at another date and time, in the
+    /// same offset.
+    pub(crate) fn moved_to(&self, t: NaiveDateTime) -> Self {
+        match self {
+            Self::Timed(_) => Self::Timed(t),
+            Self::Zoned(old) => old
+                .timezone()
+                .from_local_datetime(&t)
+                .single()
+                .map_or(Self::Timed(t), Self::Zoned),
+            _ => Self::Day(t),
+        }
+    }
+}
+
+/// Returns when a day deadline falls due, given the policy.
+fn day_boundary(day: NaiveDate, policy: DuePolicy) -> Option<NaiveDateTime> {
+    let day = match policy {
+        DuePolicy::StartOfDay => day,
+        DuePolicy::EndOfDay => day.succ_opt()?,
+    };
+
+    Some(day.and_time(NaiveTime::MIN))
+}
+
+/// Returns a local time in a timezone, skipping over a gap left by daylight
+/// saving time.
+fn in_timezone<Tz: TimeZone>(tz: &Tz, t: NaiveDateTime) -> Option<DateTime<Tz>> {
+    tz.from_local_datetime(&t)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(t + Duration::hours(1))).earliest())
+}
+
+/// Formats a date and time, with seconds only if there are any.
+fn format_time(t: NaiveDateTime) -> String {
+    if t.second() == 0 {
+        t.format("%FT%H:%M").to_string()
+    } else {
+        t.format("%FT%H:%M:%S").to_string()
+    }
+}
+
+/// Parses a date and time with an optional offset, such as
+/// `2024-05-01T14:30` or `2024-05-01T14:30:15+02:00`.
+pub(crate) fn parse_time(s: &str) -> Option<TodoDate> {
+    let (date, time) = s.split_once(['T', 't'])?;
+    let date = NaiveDate::parse_from_str(date, "%F").ok()?;
+
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => (&time[..i], Some(parse_offset(&time[i..])?)),
+        None => (time, None),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .ok()?;
+
+    let t = date.and_time(time);
+    match offset {
+        Some(offset) => offset.from_local_datetime(&t).single().map(TodoDate::Zoned),
+        None => Some(TodoDate::Timed(t)),
+    }
+}
+
+/// Parses `Z`, `+HH:MM`, `+HHMM` or `+HH`.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    if s.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let sign = match s.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = s[1..].replacen(':', "", 1);
+    if !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    let (hours, minutes) = match digits.len() {
+        2 => (&digits[..], "0"),
+        4 => digits.split_at(2),
+        _ => return None,
+    };
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
 }
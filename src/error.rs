@@ -17,10 +17,11 @@ impl Display for InvalidPriorityError {
 
 impl Error for InvalidPriorityError {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TodoParseError {
     BadDate,
     BadPriority,
+    BadRecurrence,
 }
 
 impl Display for TodoParseError {
@@ -28,8 +29,200 @@ impl Display for TodoParseError {
         match self {
             Self::BadDate => write!(f, "Invalid date"),
             Self::BadPriority => write!(f, "Invalid priority"),
+            Self::BadRecurrence => write!(f, "Invalid recurrence"),
         }
     }
 }
 
 impl Error for TodoParseError {}
+
+/// A parse error on a specific line of a todo.txt file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineError {
+    /// The 1-based line number.
+    pub line: usize,
+    pub error: TodoParseError,
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+impl Error for LineError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    UnclosedParen,
+    UnclosedQuote,
+    BadDate(String),
+    BadPriority(String),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "Unexpected end of query"),
+            Self::UnexpectedToken(t) => write!(f, "Unexpected `{t}` in query"),
+            Self::UnclosedParen => write!(f, "Missing closing parenthesis in query"),
+            Self::UnclosedQuote => write!(f, "Missing closing quote in query"),
+            Self::BadDate(d) => write!(f, "Invalid date `{d}` in query"),
+            Self::BadPriority(p) => write!(f, "Invalid priority `{p}` in query"),
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+/// An error parsing a `TodoTable` from its todo.txt or Markdown format.
+#[derive(Clone, Debug)]
+pub enum TableParseError {
+    /// A todo failed to parse.
+    Todo(LineError),
+    /// A todo appeared before any column header, on the given line.
+    MissingColumn(usize),
+}
+
+impl Display for TableParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Todo(e) => write!(f, "{e}"),
+            Self::MissingColumn(line) => write!(f, "Line {line}: Todo outside of any column"),
+        }
+    }
+}
+
+impl Error for TableParseError {}
+
+/// An error parsing an iCalendar document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ICalError {
+    /// A line that isn't a `NAME:value` content line.
+    BadLine(String),
+    /// An `END` that doesn't match the open component.
+    UnexpectedEnd(String),
+    /// A component that is never closed.
+    Unterminated(String),
+    BadDate(String),
+    BadPriority(String),
+    /// There is no `VTODO` component.
+    MissingTodo,
+}
+
+impl Display for ICalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadLine(l) => write!(f, "Invalid iCalendar line `{l}`"),
+            Self::UnexpectedEnd(c) => write!(f, "Unexpected `END:{c}`"),
+            Self::Unterminated(c) => write!(f, "Missing `END:{c}`"),
+            Self::BadDate(d) => write!(f, "Invalid iCalendar date `{d}`"),
+            Self::BadPriority(p) => write!(f, "Invalid iCalendar priority `{p}`"),
+            Self::MissingTodo => write!(f, "No VTODO component"),
+        }
+    }
+}
+
+impl Error for ICalError {}
+
+/// An error parsing CSV.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CsvError {
+    /// There is no header row.
+    MissingHeader,
+    /// A quoted field isn't closed, in the record starting on the given line.
+    UnclosedQuote(usize),
+    /// A field has an invalid value, in the record starting on the given line.
+    BadValue {
+        line: usize,
+        column: String,
+        value: String,
+    },
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "Missing CSV header row"),
+            Self::UnclosedQuote(line) => write!(f, "Line {line}: Missing closing quote"),
+            Self::BadValue {
+                line,
+                column,
+                value,
+            } => write!(f, "Line {line}: Invalid {column} `{value}`"),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+/// An error importing Taskwarrior JSON.
+#[cfg(feature = "taskwarrior")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskwarriorError {
+    /// The JSON is malformed, or isn't an array.
+    Json(String),
+    /// A task isn't a JSON object.
+    NotATask,
+    BadDate(String),
+    BadPriority(String),
+}
+
+#[cfg(feature = "taskwarrior")]
+impl Display for TaskwarriorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Invalid Taskwarrior JSON: {e}"),
+            Self::NotATask => write!(f, "Taskwarrior task isn't an object"),
+            Self::BadDate(d) => write!(f, "Invalid Taskwarrior date `{d}`"),
+            Self::BadPriority(p) => write!(f, "Invalid Taskwarrior priority `{p}`"),
+        }
+    }
+}
+
+#[cfg(feature = "taskwarrior")]
+impl Error for TaskwarriorError {}
+
+/// An error parsing a theme config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThemeError {
+    /// A line isn't blank, a comment or `key = value`.
+    BadLine(usize),
+    BadStyle {
+        line: usize,
+        value: String,
+    },
+    /// A color mode isn't `always`, `never` or `auto`.
+    BadColorMode {
+        line: usize,
+        value: String,
+    },
+    UnknownTheme {
+        line: usize,
+        name: String,
+    },
+    UnknownVariable {
+        line: usize,
+        name: String,
+    },
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadLine(line) => write!(f, "Line {line}: Expected `key = value`"),
+            Self::BadStyle { line, value } => write!(f, "Line {line}: Invalid style `{value}`"),
+            Self::BadColorMode { line, value } => {
+                write!(f, "Line {line}: Invalid color mode `{value}`")
+            }
+            Self::UnknownTheme { line, name } => write!(f, "Line {line}: Unknown theme `{name}`"),
+            Self::UnknownVariable { line, name } => {
+                write!(f, "Line {line}: Unknown variable `{name}`")
+            }
+        }
+    }
+}
+
+impl Error for ThemeError {}
@@ -1,11 +1,30 @@
 use std::fmt::Display;
 
+use chrono::NaiveDateTime;
+
+use crate::clock::{Clock, SystemClock};
+use crate::due::DuePolicy;
+
 pub trait IsDue {
     /// Returns whether or not a date is past it
-    fn due(&self) -> bool;
+    fn due(&self) -> bool {
+        self.due_at(SystemClock.now())
+    }
+
+    /// Returns whether or not a date is past it at the given time.
+    fn due_at(&self, now: NaiveDateTime) -> bool;
+
+    /// Returns whether or not a date is past it at the given time, with a
+    /// policy for deadlines without a time.
+    ///
+    /// Defaults to `due_at`, which uses `DuePolicy::default()`.
+    fn due_with(&self, now: NaiveDateTime, policy: DuePolicy) -> bool {
+        let _ = policy;
+        self.due_at(now)
+    }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Map<K: PartialEq, V: PartialEq> {
     pub data: Vec<(K, V)>,
 }
@@ -66,3 +85,54 @@ impl<K: PartialEq, V: PartialEq> Map<K, V> {
         self.data.is_empty()
     }
 }
+
+/// Returns true if the text can be written as the key or value of a
+/// `key:value` tag.
+pub(crate) fn is_meta_value(s: &str) -> bool {
+    !s.is_empty() && !s.contains(':') && !s.chars().any(|ch| ch.is_whitespace())
+}
+
+/// Percent-encodes the characters which can't appear in todo.txt metadata.
+pub(crate) fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if ch == '%' || ch == ':' || ch.is_whitespace() {
+            let mut buf = [0; 4];
+            for byte in ch.encode_utf8(&mut buf).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            encoded.push(ch);
+        }
+    }
+
+    encoded
+}
+
+/// Decodes text encoded by `percent_encode`, leaving invalid escapes as-is.
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| s.to_string())
+}
@@ -0,0 +1,214 @@
+//! HTML rendering.
+//!
+//! `Html` renders every element of a todo as a `<span>` with a CSS class, so
+//! that pages can be styled with a stylesheet instead of `StyleScheme`:
+//!
+//! - `todo-tick`, `todo-priority`, `todo-completion` and `todo-creation`
+//! - `todo-description`, for the plain text
+//! - `todo-project` and `todo-context`, for tags
+//! - `todo-deadline`, `todo-threshold` and `todo-metadata`
+//!
+//! The todo itself is a `<span class="todo">`, also given the classes
+//! `todo-completed`, `todo-due`, `todo-hidden` and `todo-priority-a` to
+//! `todo-priority-z` when they apply; whether a todo is due or hidden is
+//! decided as of `Html::now`. The text of the whole todo reads as its
+//! todo.txt line.
+//!
+//! Columns are `<section class="todo-column">` with a `<h2>` title and a
+//! `<ul>` of todos, subtasks nested below their parents. Tables are
+//! `<div class="todo-table">` with a `<h1>` title.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::html;
+//!
+//! let todo = Todo::from_str("(A) Fix <br> tags +web").unwrap();
+//! assert_eq!(
+//!     html::todo_to_html(&todo),
+//!     "<span class=\"todo todo-priority-a\">\
+//!      <span class=\"todo-priority\">(A)</span> \
+//!      <span class=\"todo-description\">Fix</span> \
+//!      <span class=\"todo-description\">&lt;br&gt;</span> \
+//!      <span class=\"todo-description\">tags</span> \
+//!      <span class=\"todo-project\">+web</span>\
+//!      </span>",
+//! );
+//! ```
+
+use chrono::NaiveDateTime;
+
+use crate::clock::{Clock, SystemClock};
+use crate::helper::IsDue;
+use crate::render::{Element, Renderer};
+use crate::{Todo, TodoColumn, TodoTable};
+
+/// A basic stylesheet for the classes used by this module.
+pub const DEFAULT_CSS: &str = "\
+.todo-table { font-family: sans-serif; display: flex; gap: 2em; flex-wrap: wrap; }
+.todo-column ul { list-style: none; padding-left: 1em; }
+.todo-completed { opacity: 0.5; text-decoration: line-through; }
+.todo-due .todo-deadline { color: #c00; font-weight: bold; }
+.todo-priority { font-weight: bold; color: #088; }
+.todo-completion, .todo-creation { color: #808; }
+.todo-project { font-style: italic; color: #a80; }
+.todo-context { font-style: italic; color: #080; }
+.todo-deadline { color: #c00; }
+.todo-threshold, .todo-metadata { font-style: italic; color: #448; }
+";
+
+/// Renders todos, columns and tables as HTML.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Html {
+    /// The time as of which todos are due or hidden.
+    pub now: NaiveDateTime,
+}
+
+impl Default for Html {
+    fn default() -> Self {
+        Self::at(SystemClock.now())
+    }
+}
+
+impl Html {
+    /// Returns a renderer as of the current time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a renderer as of the given time.
+    pub fn at(now: NaiveDateTime) -> Self {
+        Self { now }
+    }
+}
+
+impl Renderer for Html {
+    fn element(&self, _: &Todo, element: Element, text: &str) -> String {
+        format!("<span class=\"{}\">{}</span>", class(element), escape(text))
+    }
+
+    fn todo(&self, todo: &Todo, elements: Vec<(Element, String)>) -> String {
+        let mut classes = vec!["todo".to_string()];
+        if todo.completed {
+            classes.push("todo-completed".to_string());
+        }
+        if todo.due_at(self.now) {
+            classes.push("todo-due".to_string());
+        }
+        if todo.is_hidden_at(self.now) {
+            classes.push("todo-hidden".to_string());
+        }
+        if todo.priority.is_some() {
+            let letter = (todo.priority as u8 as char).to_ascii_lowercase();
+            classes.push(format!("todo-priority-{letter}"));
+        }
+
+        let elements: Vec<String> = elements.into_iter().map(|(_, html)| html).collect();
+        format!(
+            "<span class=\"{}\">{}</span>",
+            classes.join(" "),
+            elements.join(" ")
+        )
+    }
+
+    fn column(&self, col: &TodoColumn, todos: Vec<(usize, String)>) -> String {
+        let mut s = format!(
+            "<section class=\"todo-column\">\n<h2>{}</h2>\n<ul>",
+            escape(&col.title)
+        );
+
+        let mut prev: Option<usize> = None;
+        for (depth, todo) in todos {
+            match prev {
+                Some(prev) if depth > prev => s.push_str("\n<ul>"),
+                Some(prev) => {
+                    s.push_str("</li>");
+                    for _ in depth..prev {
+                        s.push_str("\n</ul></li>");
+                    }
+                }
+                None => {}
+            }
+
+            s.push_str(&format!("\n<li>{todo}"));
+            prev = Some(depth);
+        }
+
+        if let Some(prev) = prev {
+            s.push_str("</li>");
+            for _ in 0..prev {
+                s.push_str("\n</ul></li>");
+            }
+        }
+
+        s.push_str("\n</ul>\n</section>\n");
+        s
+    }
+
+    fn table(&self, table: &TodoTable, columns: Vec<String>) -> String {
+        format!(
+            "<div class=\"todo-table\">\n<h1>{}</h1>\n{}</div>\n",
+            escape(table.title()),
+            columns.concat()
+        )
+    }
+}
+
+/// Returns a todo as a `<span>` of classed `<span>`s.
+pub fn todo_to_html(todo: &Todo) -> String {
+    todo.render(&Html::new())
+}
+
+/// Returns a column as a `<section>` with a heading and a list of todos.
+pub fn column_to_html(col: &TodoColumn) -> String {
+    col.render(&Html::new())
+}
+
+/// Returns a table as a `<div>` with a heading and its columns.
+pub fn table_to_html(table: &TodoTable) -> String {
+    table.render(&Html::new())
+}
+
+/// Returns a standalone HTML document showing a table, styled with
+/// `DEFAULT_CSS`.
+pub fn page(table: &TodoTable) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{DEFAULT_CSS}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(table.title()),
+        table_to_html(table),
+    )
+}
+
+/// Escapes text for use in HTML content and attributes.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Returns the CSS class of an element.
+fn class(element: Element) -> &'static str {
+    match element {
+        Element::Tick => "todo-tick",
+        Element::Priority => "todo-priority",
+        Element::Completion => "todo-completion",
+        Element::Creation => "todo-creation",
+        Element::Description => "todo-description",
+        Element::Project => "todo-project",
+        Element::Context => "todo-context",
+        Element::Deadline => "todo-deadline",
+        Element::Threshold => "todo-threshold",
+        Element::Metadata => "todo-metadata",
+    }
+}
@@ -0,0 +1,536 @@
+//! iCalendar (RFC 5545) `VTODO` import and export.
+//!
+//! Todos map onto `VTODO` properties as follows:
+//!
+//! - description: `SUMMARY`, with its tags also in `CATEGORIES` as
+//!   `+project` and `@context`
+//! - `priority`: `PRIORITY`, with A to H as 1 to 8 and I to Z as 9
+//! - `deadline`: `DUE`
+//! - `creation`: `DTSTART` and `CREATED`
+//! - `completed` and `completion_date`: `STATUS` and `COMPLETED`
+//! - `threshold`: `X-TODOTXT-THRESHOLD`
+//! - the `uid` metadata key: `UID`, percent-encoded like metadata in
+//!   `taskwarrior`, or generated from the description and creation date if
+//!   missing, so that it's the same on every export. Identical todos in one
+//!   export get distinct UIDs by their order; use `assign_uids` to store
+//!   the generated UIDs so they survive reordering
+//! - other metadata: one `X-TODOTXT-META` per `key:value` pair
+//!
+//! `TodoDate::Always` has no iCalendar equivalent, and isn't exported.
+//!
+//! When importing, categories without a `+` or `@` prefix become contexts,
+//! `PRIORITY:0` means no priority, and `TZID` parameters are ignored, so
+//! such times are read as local times. UTC times are read as local times
+//! too, except for `DUE`, which becomes a `TodoDate::Zoned` deadline.
+//! Components other than `VTODO` are skipped.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::ical;
+//!
+//! let todo = Todo::from_str("(A) Call mom +family @phone due:2024-01-31").unwrap();
+//! let ics = ical::to_ics([&todo]);
+//!
+//! assert!(ics.contains("PRIORITY:1\r\n"));
+//! assert!(ics.contains("DUE;VALUE=DATE:20240131\r\n"));
+//! assert!(ics.contains("CATEGORIES:+family,@phone\r\n"));
+//!
+//! let todos = ical::parse_ics(&ics).unwrap();
+//! assert_eq!(todos[0].to_string(), format!("{todo} uid:{}", todos[0].get_meta("uid").unwrap()));
+//! ```
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::ICalError;
+use crate::helper::{is_meta_value, percent_decode, percent_encode};
+use crate::{Todo, TodoDate, TodoPriority, TodoSegment, TodoTag};
+
+/// The longest a line may be, in bytes, before it's folded.
+const MAX_LINE: usize = 75;
+
+/// Returns a todo as a `VTODO` component, with CRLF line endings.
+pub fn to_vtodo(todo: &Todo) -> String {
+    to_vtodo_at(todo, SystemClock.now())
+}
+
+/// Returns a todo as a `VTODO` component, stamped with the given time.
+pub fn to_vtodo_at(todo: &Todo, now: NaiveDateTime) -> String {
+    let uid = match todo.get_meta("uid") {
+        Some(uid) => percent_decode(uid),
+        None => generate_uid(todo, 0),
+    };
+    vtodo(todo, &uid, now)
+}
+
+/// Returns a todo as a `VTODO` component with the given UID.
+fn vtodo(todo: &Todo, uid: &str, now: NaiveDateTime) -> String {
+    let summary = todo.description.to_string();
+    let mut lines = vec!["BEGIN:VTODO".to_string()];
+
+    lines.push(format!("UID:{}", escape(uid)));
+    lines.push(format!("DTSTAMP:{}", format_utc(now)));
+    lines.push(format!("SUMMARY:{}", escape(&summary)));
+
+    let mut categories: Vec<String> = Vec::new();
+    for seg in &todo.description.0 {
+        if let TodoSegment::Tag(tag) = seg {
+            let tag = escape(&tag.to_string());
+            if !categories.contains(&tag) {
+                categories.push(tag);
+            }
+        }
+    }
+    if !categories.is_empty() {
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+
+    if todo.priority.is_some() {
+        let level = (todo.priority as u8 - b'A' + 1).min(9);
+        lines.push(format!("PRIORITY:{level}"));
+    }
+
+    match todo.deadline {
+        TodoDate::Day(date) => lines.push(format!("DUE;VALUE=DATE:{}", date.format("%Y%m%d"))),
+        TodoDate::Timed(date) => lines.push(format!("DUE:{}", date.format("%Y%m%dT%H%M%S"))),
+        TodoDate::Zoned(date) => lines.push(format!(
+            "DUE:{}",
+            date.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+        )),
+        _ => {}
+    }
+
+    if let Some(date) = todo.creation {
+        lines.push(format!("DTSTART{}", format_date(date)));
+        lines.push(format!("CREATED:{}", format_utc(date)));
+    }
+
+    if todo.completed {
+        lines.push("STATUS:COMPLETED".to_string());
+        if let Some(date) = todo.completion_date {
+            lines.push(format!("COMPLETED:{}", format_utc(date)));
+        }
+    } else {
+        lines.push("STATUS:NEEDS-ACTION".to_string());
+    }
+
+    if let Some(date) = todo.threshold {
+        lines.push(format!("X-TODOTXT-THRESHOLD{}", format_date(date)));
+    }
+
+    for (key, val) in &todo.metadata.data {
+        if key != "uid" {
+            lines.push(format!(
+                "X-TODOTXT-META:{}",
+                escape(&format!("{key}:{val}"))
+            ));
+        }
+    }
+
+    lines.push("END:VTODO".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Returns the todos as an iCalendar document, with CRLF line endings.
+pub fn to_ics<'a, I: IntoIterator<Item = &'a Todo>>(todos: I) -> String {
+    to_ics_at(todos, SystemClock.now())
+}
+
+/// Returns the todos as an iCalendar document, stamped with the given time.
+pub fn to_ics_at<'a, I: IntoIterator<Item = &'a Todo>>(todos: I, now: NaiveDateTime) -> String {
+    let mut ics = fold("BEGIN:VCALENDAR");
+    ics.push_str(&fold("VERSION:2.0"));
+    ics.push_str(&fold("PRODID:-//todo-lib//todo-lib//EN"));
+    let todos: Vec<&Todo> = todos.into_iter().collect();
+    for (todo, uid) in todos.iter().zip(uids(&todos)) {
+        ics.push_str(&vtodo(todo, &uid, now));
+    }
+    ics.push_str(&fold("END:VCALENDAR"));
+
+    ics
+}
+
+/// Stores the UID each todo without a `uid` would be exported with as its
+/// `uid` metadata, so that later exports keep it even if the todos are
+/// reordered or edited.
+pub fn assign_uids<'a, I: IntoIterator<Item = &'a mut Todo>>(todos: I) {
+    let mut todos: Vec<&mut Todo> = todos.into_iter().collect();
+    let generated = uids(&todos.iter().map(|todo| &**todo).collect::<Vec<_>>());
+    for (todo, uid) in todos.iter_mut().zip(generated) {
+        if todo.get_meta("uid").is_none() {
+            todo.set_meta("uid".to_string(), percent_encode(&uid));
+        }
+    }
+}
+
+/// Returns the UID of each todo, generating distinct ones for todos
+/// without a `uid`.
+fn uids(todos: &[&Todo]) -> Vec<String> {
+    let mut used: HashSet<String> = todos
+        .iter()
+        .filter_map(|todo| todo.get_meta("uid"))
+        .map(|uid| percent_decode(uid))
+        .collect();
+
+    todos
+        .iter()
+        .map(|todo| match todo.get_meta("uid") {
+            Some(uid) => percent_decode(uid),
+            None => {
+                let uid = (0..)
+                    .map(|n| generate_uid(todo, n))
+                    .find(|uid| !used.contains(uid))
+                    .unwrap();
+                used.insert(uid.clone());
+                uid
+            }
+        })
+        .collect()
+}
+
+/// Generates a UID from a todo's description and creation date, and for
+/// `n > 0`, a counter telling identical todos apart.
+fn generate_uid(todo: &Todo, n: usize) -> String {
+    let creation = todo.creation.map(|date| date.format("%FT%T").to_string());
+    let mut key = format!("{}\0{}", todo.description, creation.unwrap_or_default());
+    if n > 0 {
+        key.push_str(&format!("\0{n}"));
+    }
+    format!("{:016x}@todo-lib", fnv1a(key.as_bytes()))
+}
+
+/// Parses the first `VTODO` component in the text.
+pub fn from_vtodo<S: AsRef<str>>(text: S) -> Result<Todo, ICalError> {
+    parse_ics(text)?
+        .into_iter()
+        .next()
+        .ok_or(ICalError::MissingTodo)
+}
+
+/// Parses every `VTODO` component of an iCalendar document.
+pub fn parse_ics<S: AsRef<str>>(text: S) -> Result<Vec<Todo>, ICalError> {
+    let mut todos = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut props = Vec::new();
+
+    for line in unfold(text.as_ref()) {
+        let (name, value) = split_line(&line)?;
+        match name.as_str() {
+            "BEGIN" => components.push(value.to_uppercase()),
+            "END" => match components.pop() {
+                Some(c) if c.eq_ignore_ascii_case(value) => {
+                    if c == "VTODO" {
+                        todos.push(todo_from_props(std::mem::take(&mut props))?);
+                    }
+                }
+                _ => return Err(ICalError::UnexpectedEnd(value.to_string())),
+            },
+            _ if components.last().is_some_and(|c| c == "VTODO") => {
+                props.push((name, value.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    match components.pop() {
+        Some(c) => Err(ICalError::Unterminated(c)),
+        None => Ok(todos),
+    }
+}
+
+/// Reads the todos of an iCalendar document.
+///
+/// Parse errors are returned as `io::ErrorKind::InvalidData`.
+pub fn read<R: Read>(mut reader: R) -> io::Result<Vec<Todo>> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_ics(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Reads the todos of an `.ics` file.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Todo>> {
+    read(File::open(path)?)
+}
+
+/// Writes the todos as an iCalendar document.
+pub fn write<'a, W: Write, I: IntoIterator<Item = &'a Todo>>(
+    mut writer: W,
+    todos: I,
+) -> io::Result<()> {
+    writer.write_all(to_ics(todos).as_bytes())?;
+    writer.flush()
+}
+
+/// Writes the todos to an `.ics` file, creating or truncating it.
+pub fn save<'a, P: AsRef<Path>, I: IntoIterator<Item = &'a Todo>>(
+    path: P,
+    todos: I,
+) -> io::Result<()> {
+    write(BufWriter::new(File::create(path)?), todos)
+}
+
+/// Builds a todo from the properties of a `VTODO` component.
+fn todo_from_props(props: Vec<(String, String)>) -> Result<Todo, ICalError> {
+    let get = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+
+    let summary = unescape(get("SUMMARY").unwrap_or_default()).replace(['\r', '\n'], " ");
+    let mut todo = Todo::new(summary, TodoDate::Never, TodoPriority::None);
+    todo.creation = None;
+
+    if let Some(uid) = get("UID").map(unescape).filter(|uid| !uid.is_empty()) {
+        todo.set_meta("uid".to_string(), percent_encode(&uid));
+    }
+
+    if let Some(level) = get("PRIORITY") {
+        let level: u8 = level
+            .trim()
+            .parse()
+            .map_err(|_| ICalError::BadPriority(level.to_string()))?;
+
+        todo.priority = match level {
+            0 => TodoPriority::None,
+            1..=9 => TodoPriority::try_from(format!("({})", (b'A' + level - 1) as char).as_str())
+                .map_err(|_| ICalError::BadPriority(level.to_string()))?,
+            _ => return Err(ICalError::BadPriority(level.to_string())),
+        };
+    }
+
+    if let Some(date) = get("DUE") {
+        todo.deadline = parse_deadline(date)?;
+    }
+
+    if let Some(date) = get("DTSTART").or_else(|| get("CREATED")) {
+        todo.creation = Some(parse_date(date)?);
+    }
+
+    if let Some(date) = get("COMPLETED") {
+        todo.completed = true;
+        todo.completion_date = Some(parse_date(date)?);
+    }
+    if get("STATUS").is_some_and(|s| s.eq_ignore_ascii_case("COMPLETED")) {
+        todo.completed = true;
+    }
+
+    if let Some(date) = get("X-TODOTXT-THRESHOLD") {
+        todo.threshold = Some(parse_date(date)?);
+    }
+
+    for (name, value) in &props {
+        match name.as_str() {
+            "CATEGORIES" => {
+                for category in split_list(value) {
+                    let tag = match (category.strip_prefix('+'), category.strip_prefix('@')) {
+                        (Some(project), _) => TodoTag::project(project),
+                        (_, Some(context)) => TodoTag::context(context),
+                        _ => TodoTag::context(
+                            category.split_whitespace().collect::<Vec<_>>().join("-"),
+                        ),
+                    };
+
+                    if let Some(tag) = tag.filter(|tag| !todo.has_tag(tag.clone())) {
+                        todo.description.0.push(TodoSegment::Tag(tag));
+                    }
+                }
+            }
+            "X-TODOTXT-META" => {
+                if let Some((key, val)) = unescape(value).split_once(':') {
+                    if is_meta_value(key) && is_meta_value(val) {
+                        todo.set_meta(key, val);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(todo)
+}
+
+/// Formats a date as a property value, including the separating colon.
+///
+/// Midnight is written as a whole day, other times as floating times.
+fn format_date(date: NaiveDateTime) -> String {
+    if date.time() == NaiveTime::MIN {
+        format!(";VALUE=DATE:{}", date.format("%Y%m%d"))
+    } else {
+        format!(":{}", date.format("%Y%m%dT%H%M%S"))
+    }
+}
+
+/// Formats a local date as a UTC date-time.
+pub(crate) fn format_utc(date: NaiveDateTime) -> String {
+    match Local.from_local_datetime(&date).earliest() {
+        Some(date) => date
+            .with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string(),
+        None => date.format("%Y%m%dT%H%M%S").to_string(),
+    }
+}
+
+/// Parses a `DATE` or `DATE-TIME` value into a deadline, keeping UTC times
+/// in UTC.
+pub(crate) fn parse_deadline(value: &str) -> Result<TodoDate, ICalError> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map_err(|_| ICalError::BadDate(value.to_string()))?;
+        return Ok(TodoDate::Zoned(Utc.from_utc_datetime(&date).fixed_offset()));
+    }
+
+    let date = parse_date(value)?;
+    Ok(if value.contains('T') {
+        TodoDate::Timed(date)
+    } else {
+        TodoDate::Day(date)
+    })
+}
+
+/// Returns the 64-bit FNV-1a hash of some bytes. Unlike `DefaultHasher`,
+/// it doesn't change between Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Parses a `DATE` or `DATE-TIME` value into a local date.
+pub(crate) fn parse_date(value: &str) -> Result<NaiveDateTime, ICalError> {
+    let bad = || ICalError::BadDate(value.to_string());
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| bad())?;
+        Ok(Utc
+            .from_utc_datetime(&date)
+            .with_timezone(&Local)
+            .naive_local())
+    } else if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| bad())
+    } else {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .map_err(|_| bad())
+    }
+}
+
+/// Splits a content line into its upper-cased name and its value,
+/// ignoring any parameters.
+fn split_line(line: &str) -> Result<(String, &str), ICalError> {
+    let mut quoted = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let name = line[..i].split(';').next().unwrap_or_default();
+                if name.is_empty() {
+                    break;
+                }
+
+                return Ok((name.to_uppercase(), &line[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+
+    Err(ICalError::BadLine(line.to_string()))
+}
+
+/// Folds a content line to at most 75 bytes per line, ending it with CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut len = 0;
+    for ch in line.chars() {
+        if len + ch.len_utf8() > MAX_LINE {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+
+        folded.push(ch);
+        len += ch.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Joins folded lines back together, skipping empty lines.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Escapes a `TEXT` value. `\r\n` and a bare `\r` are newlines too.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Unescapes a `TEXT` value.
+fn unescape(s: &str) -> String {
+    split_escaped(s, false).pop().unwrap_or_default()
+}
+
+/// Splits a comma-separated list of `TEXT` values, unescaping each.
+fn split_list(s: &str) -> Vec<String> {
+    split_escaped(s, true)
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Unescapes a `TEXT` value, optionally splitting it on unescaped commas.
+fn split_escaped(s: &str, split: bool) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        let value = values.last_mut().expect("there is always a value");
+        match ch {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => value.push('\n'),
+                Some(ch) => value.push(ch),
+                None => value.push('\\'),
+            },
+            ',' if split => values.push(String::new()),
+            _ => value.push(ch),
+        }
+    }
+
+    values
+}
@@ -45,22 +45,48 @@ use std::{collections::HashSet, fmt::Display};
 pub use chrono;
 use chrono::prelude::*;
 
+pub mod archive;
+pub mod board;
+pub mod clock;
 pub mod colors;
+pub mod csv;
+pub mod deps;
 pub mod due;
 pub mod error;
 pub mod helper;
+pub mod html;
+pub mod ical;
+pub mod list;
+pub mod markdown;
+pub mod org;
 pub mod prelude;
 pub mod priority;
+pub mod query;
+pub mod recur;
+pub mod relative;
+pub mod render;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod sort;
 pub mod table;
+#[cfg(feature = "taskwarrior")]
+pub mod taskwarrior;
+pub mod theme;
 
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod test;
 
+use clock::{Clock, SystemClock};
 use colors::StyleScheme;
-pub use due::TodoDate;
+pub use due::{DuePolicy, TodoDate};
 use error::*;
 use helper::*;
+pub use list::{TodoLine, TodoList};
 pub use priority::TodoPriority;
+pub use recur::Recurrence;
+pub use relative::RelativeDate;
+use render::{Ansi, Element, Plain, Renderer};
 pub use table::{TodoColumn, TodoTable};
 
 /// A todo tag.
@@ -68,6 +94,8 @@ pub use table::{TodoColumn, TodoTable};
 /// NOTE: ONLY use `TodoTag::project` and `TodoTag::context` to create a tag.
 /// This ensures that the tags are valid.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum TodoTag {
     Project(String),
     Context(String),
@@ -100,50 +128,80 @@ impl TodoTag {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum TodoSegment {
     String(String),
     Tag(TodoTag),
+    /// A `key:value` pair kept at its original position.
+    ///
+    /// Only produced by `Todo::parse_lossless`; the current value lives in
+    /// `Todo::metadata`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Meta(String, String),
+    /// A `due:` token kept at its original position, along with the
+    /// deadline it was parsed into.
+    ///
+    /// Only produced by `Todo::parse_lossless`; the current value lives in
+    /// `Todo::deadline`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Due(String, TodoDate),
+    /// A `t:` token kept at its original position, along with the
+    /// threshold it was parsed into.
+    ///
+    /// Only produced by `Todo::parse_lossless`; the current value lives in
+    /// `Todo::threshold`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Threshold(String, Option<NaiveDateTime>),
 }
 
-impl TodoSegment {
-    pub fn to_string(&self, style: StyleScheme, reset: &str) -> String {
+impl Display for TodoSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TodoSegment::String(s) => format!("{}{s}{reset}", style.description),
-            TodoSegment::Tag(tag) => format!(
-                "{}{tag}{reset}",
-                match tag {
-                    TodoTag::Context(_) => style.context,
-                    TodoTag::Project(_) => style.project,
-                }
-            ),
+            Self::String(s) => write!(f, "{s}"),
+            Self::Tag(tag) => write!(f, "{tag}"),
+            Self::Meta(key, val) => write!(f, "{key}:{val}"),
+            Self::Due(raw, _) => write!(f, "due:{raw}"),
+            Self::Threshold(raw, _) => write!(f, "t:{raw}"),
         }
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct TodoDescription(Vec<TodoSegment>);
-
-impl TodoDescription {
-    pub fn to_string(&self, style: StyleScheme, reset: &str) -> String {
-        let mut s = String::new();
-
-        if self.0.is_empty() {
-            return s;
-        }
-
-        let end = self.0.len() - 1;
-        for (i, seg) in self.0.iter().enumerate() {
-            s.push_str(&seg.to_string(style, reset));
+impl TodoSegment {
+    /// Returns true if the segment is a `key:value` token kept in place.
+    pub fn is_inline_meta(&self) -> bool {
+        matches!(self, Self::Meta(..) | Self::Due(..) | Self::Threshold(..))
+    }
+}
 
-            if i != end {
-                s.push(' ');
-            }
-        }
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct TodoDescription(Vec<TodoSegment>);
 
-        s.push_str(reset);
+/// Writes the description text, without any in-place metadata.
+impl Display for TodoDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let segments: Vec<_> = self
+            .0
+            .iter()
+            .filter(|seg| !seg.is_inline_meta())
+            .map(|seg| seg.to_string())
+            .collect();
+
+        write!(f, "{}", segments.join(" "))
+    }
+}
 
-        s
+impl TodoDescription {
+    /// Returns true if the given metadata key is kept in place.
+    fn has_inline_meta(&self, key: &str) -> bool {
+        self.0.iter().any(|seg| match seg {
+            TodoSegment::Meta(k, _) => k == key,
+            TodoSegment::Due(..) => key == "due",
+            TodoSegment::Threshold(..) => key == "t",
+            _ => false,
+        })
     }
 }
 
@@ -169,7 +227,9 @@ impl TodoDescription {
 /// assert!(todo.completed && !todo.due());
 /// ```
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Todo {
     pub description: TodoDescription,
 
@@ -178,6 +238,8 @@ pub struct Todo {
     pub metadata: Map<String, String>,
 
     pub deadline: TodoDate,
+    /// The `t:` threshold date, before which the todo is hidden.
+    pub threshold: Option<NaiveDateTime>,
     pub creation: Option<NaiveDateTime>,
     pub completion_date: Option<NaiveDateTime>,
 }
@@ -185,6 +247,16 @@ pub struct Todo {
 impl Todo {
     /// Returns a new todo.
     pub fn new<S: AsRef<str>>(title: S, deadline: TodoDate, priority: TodoPriority) -> Self {
+        Self::new_at(title, deadline, priority, SystemClock.now())
+    }
+
+    /// Returns a new todo, created at the given time.
+    pub fn new_at<S: AsRef<str>>(
+        title: S,
+        deadline: TodoDate,
+        priority: TodoPriority,
+        now: NaiveDateTime,
+    ) -> Self {
         let mut description = Vec::new();
         for part in title.as_ref().split(' ') {
             if let Some(context) = part.strip_prefix('@') {
@@ -198,7 +270,8 @@ impl Todo {
 
         Todo {
             deadline,
-            creation: Some(Local::now().naive_local()),
+            threshold: None,
+            creation: Some(now),
 
             completed: false,
             priority,
@@ -212,21 +285,136 @@ impl Todo {
     /// Sets the creation date to current day.
     /// Helpful for parsing from a string.
     pub fn set_creation(&mut self) {
-        self.creation = Some(Local::now().naive_local());
+        self.set_creation_at(SystemClock.now());
+    }
+
+    /// Sets the creation date to the given time.
+    pub fn set_creation_at(&mut self, now: NaiveDateTime) {
+        self.creation = Some(now);
+    }
+
+    /// Rewrites relative `due:` and `t:` tokens kept in place by
+    /// `Todo::parse_lossless` as the dates they resolved to.
+    ///
+    /// A relative date in a todo without a creation date resolves against
+    /// the day it's read, so pinning it before saving keeps it from moving.
+    ///
+    /// Example:
+    /// ```
+    /// use todo_lib::prelude::*;
+    ///
+    /// let mut todo = Todo::parse_lossless("Call  mom due:tomorrow").unwrap();
+    /// assert_eq!(todo.to_string(), "Call  mom due:tomorrow");
+    ///
+    /// todo.pin_relative_dates();
+    /// let tomorrow = Local::now().date_naive().succ_opt().unwrap();
+    /// assert_eq!(todo.to_string(), format!("Call  mom due:{tomorrow}"));
+    /// ```
+    pub fn pin_relative_dates(&mut self) {
+        let relative = |raw: &str| {
+            raw.parse::<RelativeDate>()
+                .is_ok_and(|date| date.is_relative())
+        };
+
+        for seg in &mut self.description.0 {
+            match seg {
+                TodoSegment::Due(raw, parsed) if relative(raw) => *raw = parsed.value(),
+                TodoSegment::Threshold(raw, Some(date)) if relative(raw) => {
+                    *raw = date.format("%F").to_string()
+                }
+                _ => {}
+            }
+        }
     }
 
     /// Marks the todo as complete.
     ///
     /// Sets completion date to current day. Also moves
     /// priority into metadata `pri`.
+    ///
+    /// Completing a todo which is already complete refreshes its
+    /// completion date. See `complete_recurring` for todos with a `rec:`
+    /// tag.
     pub fn complete(&mut self) {
+        self.complete_at(SystemClock.now());
+    }
+
+    /// Marks the todo as complete, like `complete`.
+    ///
+    /// If the todo has a valid `rec:` tag and wasn't complete yet, returns
+    /// the next instance.
+    pub fn complete_recurring(&mut self) -> Option<Todo> {
+        self.complete_at(SystemClock.now())
+    }
+
+    /// Marks the todo as complete at the given time, like
+    /// `complete_recurring`.
+    pub fn complete_at(&mut self, now: NaiveDateTime) -> Option<Todo> {
+        let next = if self.completed {
+            None
+        } else {
+            self.next_instance(now)
+        };
+
         self.completed = true;
-        self.completion_date = Some(Local::now().naive_local());
+        self.completion_date = Some(now);
 
         if self.priority.is_some() {
-            self.metadata.insert("pri".to_string(), (self.priority as u8 as char).to_string());
+            self.metadata
+                .insert("pri".to_string(), (self.priority as u8 as char).to_string());
             self.priority = TodoPriority::None;
         }
+
+        next
+    }
+
+    /// Returns the todo's recurrence, if it has a valid `rec:` tag.
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.get_meta("rec")?.parse().ok()
+    }
+
+    /// Returns the next instance of a recurring todo completed at `now`.
+    ///
+    /// The deadline moves forward from the completion day, or from the
+    /// previous deadline for strict (`rec:+`) recurrences. The threshold
+    /// keeps its distance to the deadline.
+    fn next_instance(&self, now: NaiveDateTime) -> Option<Todo> {
+        let rec = self.recurrence()?;
+        let today = now.date().and_time(NaiveTime::MIN);
+
+        // Deadlines keep their time of day and offset.
+        let current = match &self.deadline {
+            TodoDate::Day(date) | TodoDate::Timed(date) => Some(*date),
+            TodoDate::Zoned(date) => Some(date.naive_local()),
+            _ => None,
+        };
+        let base = match current {
+            Some(date) if rec.strict => date,
+            Some(date) => today.date().and_time(date.time()),
+            None => today,
+        };
+
+        let mut next = self.clone();
+        next.completed = false;
+        next.completion_date = None;
+        let deadline = rec.apply(base)?;
+        next.deadline = self.deadline.moved_to(deadline);
+
+        if let Some(threshold) = self.threshold {
+            next.threshold = match current {
+                _ if rec.strict => rec.apply(threshold),
+                Some(date) => Some(deadline - (date - threshold)),
+                None => rec.apply(today),
+            };
+        }
+        if next.creation.is_some() {
+            next.creation = Some(now);
+        }
+
+        // The next instance is a different todo.
+        next.metadata.remove(&"id".to_string());
+
+        Some(next)
     }
 
     /// Checks if the todo has a certain project tag.
@@ -293,131 +481,267 @@ impl Todo {
         self.metadata.get(&key.to_string())
     }
 
+    /// Returns the todo's `id:` tag, if it has one.
+    pub fn id(&self) -> Option<&String> {
+        self.get_meta("id")
+    }
+
+    /// Sets the todo's `id:` tag.
+    pub fn set_id<S: ToString>(&mut self, id: S) {
+        self.metadata.insert("id".to_string(), id.to_string());
+    }
+
+    /// Returns the ID in the todo's `parent:` tag, if it has one.
+    pub fn parent(&self) -> Option<&String> {
+        self.get_meta("parent")
+    }
+
+    /// Sets the todo's `parent:` tag, making it a subtask.
+    pub fn set_parent<S: ToString>(&mut self, id: S) {
+        self.metadata.insert("parent".to_string(), id.to_string());
+    }
+
+    /// Returns the IDs in the todo's `dep:` tag, i.e. the todos it depends on.
+    pub fn dependencies(&self) -> Vec<&str> {
+        split_ids(self.get_meta("dep"))
+    }
+
+    /// Returns the IDs in the todo's `blocks:` tag, i.e. the todos which
+    /// depend on it.
+    pub fn blocks(&self) -> Vec<&str> {
+        split_ids(self.get_meta("blocks"))
+    }
+
+    /// Adds an ID to the todo's `dep:` tag.
+    pub fn add_dependency<S: AsRef<str>>(&mut self, id: S) {
+        let mut deps = self.dependencies();
+        if !deps.contains(&id.as_ref()) {
+            deps.push(id.as_ref());
+            let deps = deps.join(",");
+            self.metadata.insert("dep".to_string(), deps);
+        }
+    }
+
     /// Removes the given metadata from the todo, if it exists.
     pub fn delete_meta(&mut self, key: &String) {
         self.metadata.remove(key);
     }
 
+    /// Returns true if the todo's threshold date is still in the future.
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden_at(SystemClock.now())
+    }
+
+    /// Returns true if the todo's threshold date is after the given time's
+    /// day.
+    pub fn is_hidden_at(&self, now: NaiveDateTime) -> bool {
+        self.threshold.is_some_and(|date| date.date() > now.date())
+    }
+
+    /// Returns true if the todo can be worked on, i.e. it isn't
+    /// complete and isn't hidden by its threshold date.
+    pub fn is_actionable(&self) -> bool {
+        self.is_actionable_at(SystemClock.now())
+    }
+
+    /// Returns true if the todo can be worked on at the given time.
+    pub fn is_actionable_at(&self, now: NaiveDateTime) -> bool {
+        !self.completed && !self.is_hidden_at(now)
+    }
+
     /// Colorizes the todo as a string.
+    ///
+    /// Shorthand for rendering with `render::Ansi`.
     pub fn colored(&self, style: StyleScheme) -> String {
-        let (reset, style) = style.get_colors(self.completed);
-
-        let tick = if self.completed {
-            format!("{}x ", style.faded)
-        } else {
-            String::new()
-        };
+        self.render(&Ansi::new(style))
+    }
 
-        let priority = if self.priority.is_some() {
-            format!("{}{}{} ", style.priority, self.priority, reset)
-        } else {
-            "".into()
-        };
+    /// Renders the todo with a renderer.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
+        let elements = self
+            .elements()
+            .into_iter()
+            .map(|(element, text)| (element, renderer.element(self, element, &text)))
+            .collect();
 
-        let completion = if self.creation.is_some() {
-            if let Some(date) = self.completion_date {
-                format!(
-                    "{}{:04}-{:02}-{:02}{reset} ",
-                    style.completion,
-                    date.year(),
-                    date.month(),
-                    date.day()
-                )
-            } else {
-                "".into()
-            }
-        } else {
-            "".into()
-        };
+        renderer.todo(self, elements)
+    }
 
-        let creation = if let Some(date) = self.creation {
-            format!(
-                "{}{:04}-{:02}-{:02}{reset} ",
-                style.creation,
-                date.year(),
-                date.month(),
-                date.day()
-            )
-        } else {
-            "".into()
-        };
+    /// Returns every element of the todo in todo.txt order, as plain text.
+    fn elements(&self) -> Vec<(Element, String)> {
+        let mut elements = Vec::new();
 
-        let metadata = format!("{}{}{reset}", style.metadata, self.metadata,);
+        if self.completed {
+            elements.push((Element::Tick, "x".to_string()));
+        }
 
-        let mut deadline = format!("{}{}{reset}", style.deadline, self.deadline);
-        if !(self.metadata.is_empty() || self.deadline.is_none()) {
-            deadline += " ";
+        if self.priority.is_some() {
+            elements.push((Element::Priority, self.priority.to_string()));
         }
 
-        let mut description = self.description.to_string(style, reset);
-        if !((self.metadata.is_empty() && self.deadline.is_none()) || self.description.0.is_empty())
-        {
-            description += " ";
+        if let (Some(_), Some(date)) = (self.creation, self.completion_date) {
+            elements.push((Element::Completion, date.format("%F").to_string()));
         }
 
-        format!("{tick}{priority}{completion}{creation}{description}{deadline}{metadata}")
-    }
-}
+        if let Some(date) = self.creation {
+            elements.push((Element::Creation, date.format("%F").to_string()));
+        }
 
-impl IsDue for Todo {
-    /// Returns true if it is currently on or past the due date,.
-    /// unless the todo is already complete.
-    fn due(&self) -> bool {
-        !self.completed && self.deadline.due()
+        elements.extend(self.body());
+        elements
     }
-}
 
-impl Display for Todo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let tick = if self.completed { "x " } else { "" };
+    /// Lays out the description, deadline and metadata.
+    ///
+    /// Tokens kept in place by `Todo::parse_lossless` are written where they
+    /// were found, everything else is appended at the end.
+    fn body(&self) -> Vec<(Element, String)> {
+        let mut parts = Vec::new();
+
+        for seg in &self.description.0 {
+            match seg {
+                TodoSegment::String(s) => parts.push((Element::Description, s.clone())),
+                TodoSegment::Tag(tag @ TodoTag::Project(_)) => {
+                    parts.push((Element::Project, tag.to_string()))
+                }
+                TodoSegment::Tag(tag @ TodoTag::Context(_)) => {
+                    parts.push((Element::Context, tag.to_string()))
+                }
+                TodoSegment::Meta(key, val) => {
+                    let Some(current) = self.metadata.get(key) else {
+                        continue;
+                    };
+
+                    let parsed = self.description.0.iter().rev().find_map(|seg| match seg {
+                        TodoSegment::Meta(k, v) if k == key => Some(v),
+                        _ => None,
+                    });
+
+                    let val = if parsed == Some(current) {
+                        val
+                    } else {
+                        current
+                    };
+                    parts.push((Element::Metadata, format!("{key}:{val}")));
+                }
+                TodoSegment::Due(raw, parsed) => {
+                    if *parsed == self.deadline {
+                        parts.push((Element::Deadline, format!("due:{raw}")));
+                    } else if self.deadline.is_some() {
+                        parts.push((Element::Deadline, self.deadline.to_string()));
+                    }
+                }
+                TodoSegment::Threshold(raw, parsed) => {
+                    if *parsed == self.threshold {
+                        parts.push((Element::Threshold, format!("t:{raw}")));
+                    } else if let Some(date) = self.threshold {
+                        parts.push((Element::Threshold, format!("t:{}", date.format("%F"))));
+                    }
+                }
+            }
+        }
 
-        let priority = if self.priority.is_some() {
-            self.priority.to_string() + " "
-        } else {
-            "".into()
-        };
+        if self.deadline.is_some() && !self.description.has_inline_meta("due") {
+            parts.push((Element::Deadline, self.deadline.to_string()));
+        }
 
-        let completion = if self.creation.is_some() {
-            if let Some(date) = self.completion_date {
-                format!("{:04}-{:02}-{:02} ", date.year(), date.month(), date.day(),)
-            } else {
-                "".into()
+        if let Some(date) = self.threshold {
+            if !self.description.has_inline_meta("t") {
+                parts.push((Element::Threshold, format!("t:{}", date.format("%F"))));
             }
-        } else {
-            "".into()
-        };
+        }
 
-        let creation = if let Some(date) = self.creation {
-            format!("{:04}-{:02}-{:02} ", date.year(), date.month(), date.day(),)
-        } else {
-            "".into()
-        };
+        for (key, val) in &self.metadata.data {
+            if !self.description.has_inline_meta(key) {
+                parts.push((Element::Metadata, format!("{key}:{val}")));
+            }
+        }
 
-        let metadata = self.metadata.to_string();
+        parts
+    }
+}
 
-        let mut deadline = self.deadline.to_string();
-        if !(metadata.is_empty() || deadline.is_empty()) {
-            deadline += " ";
-        }
+impl IsDue for Todo {
+    /// Returns true if it is on or past the due date,
+    /// unless the todo is already complete.
+    fn due_at(&self, now: NaiveDateTime) -> bool {
+        self.due_with(now, DuePolicy::default())
+    }
 
-        let mut description = self.description.to_string(StyleScheme::default(), "");
-        if !((metadata.is_empty() && deadline.is_empty()) || description.is_empty()) {
-            description += " ";
-        }
+    fn due_with(&self, now: NaiveDateTime, policy: DuePolicy) -> bool {
+        !self.completed && self.deadline.due_with(now, policy)
+    }
+}
 
-        write!(
-            f,
-            "{tick}{priority}{completion}{creation}{description}{deadline}{metadata}"
-        )
+impl Display for Todo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&Plain))
     }
 }
 
 impl FromStr for Todo {
     type Err = TodoParseError;
 
-    fn from_str(mut s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, false, None)
+    }
+}
+
+impl Todo {
+    /// Parses a todo without normalizing it.
+    ///
+    /// `key:value` and `due:` tokens stay at their original position in the
+    /// description, and spacing is kept as-is, so that a todo which isn't
+    /// modified is written back byte-for-byte identical to the input.
+    ///
+    /// Example:
+    /// ```
+    /// use todo_lib::prelude::*;
+    ///
+    /// let text = "due:2053-01-01 call  +mom  rel:family";
+    /// let mut todo = Todo::parse_lossless(text).unwrap();
+    /// assert_eq!(todo.to_string(), text);
+    ///
+    /// todo.set_meta("rel", "parents");
+    /// assert_eq!(todo.to_string(), "due:2053-01-01 call  +mom  rel:parents");
+    /// ```
+    pub fn parse_lossless<S: AsRef<str>>(s: S) -> Result<Self, TodoParseError> {
+        Self::parse(s.as_ref(), true, None)
+    }
+
+    /// Parses a todo, resolving relative dates without a creation date
+    /// against the given time instead of the current time.
+    ///
+    /// See the `relative` module for the supported dates.
+    ///
+    /// Example:
+    /// ```
+    /// use todo_lib::prelude::*;
+    /// use todo_lib::chrono::NaiveDate;
+    ///
+    /// let now = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+    /// let todo = Todo::from_str_at("Book flights due:eom t:+2w", now).unwrap();
+    ///
+    /// assert_eq!(todo.to_string(), "Book flights due:2024-03-31 t:2024-03-15");
+    /// ```
+    pub fn from_str_at<S: AsRef<str>>(s: S, now: NaiveDateTime) -> Result<Self, TodoParseError> {
+        Self::parse(s.as_ref(), false, Some(now))
+    }
+
+    fn parse(
+        mut s: &str,
+        lossless: bool,
+        now: Option<NaiveDateTime>,
+    ) -> Result<Self, TodoParseError> {
         let mut todo = Todo::default();
 
+        // Non-canonical dates would be rewritten on display.
+        let parse_date = |part: &str| {
+            NaiveDate::parse_from_str(part, "%F")
+                .ok()
+                .filter(|date| !lossless || date.format("%F").to_string() == part)
+        };
+
         if let Some(remainder) = s.strip_prefix("x ") {
             s = remainder;
             todo.completed = true;
@@ -433,27 +757,34 @@ impl FromStr for Todo {
         }
 
         if let Some(part) = parts.peek() {
-            if let Ok(date) = NaiveDate::parse_from_str(part, "%F") {
+            if let Some(date) = parse_date(part) {
                 todo.creation = Some(date.and_hms_opt(0, 0, 0).ok_or(TodoParseError::BadDate)?);
                 parts.next();
             }
         }
 
         if let Some(part) = parts.peek() {
-            if let Ok(date) = NaiveDate::parse_from_str(part, "%F") {
-                todo.completion_date = std::mem::replace(
-                    &mut todo.creation,
-                    Some(date.and_hms_opt(0, 0, 0).ok_or(TodoParseError::BadDate)?),
-                );
+            if let Some(date) = parse_date(part) {
+                todo.completion_date = todo
+                    .creation
+                    .replace(date.and_hms_opt(0, 0, 0).ok_or(TodoParseError::BadDate)?);
                 parts.next();
             }
         }
 
         for part in parts {
-            if part.matches(':').count() == 1 {
-                let meta: Vec<&str> = part.split(':').collect();
-                todo.metadata
-                    .insert(meta[0].to_string(), meta[1].to_string());
+            // Deadlines with a time are the only values with colons.
+            let timed = part
+                .strip_prefix("due:")
+                .is_some_and(|raw| due::parse_time(raw).is_some());
+
+            if part.matches(':').count() == 1 || timed {
+                let (key, val) = part.split_once(':').unwrap_or_default();
+                todo.metadata.insert(key.to_string(), val.to_string());
+
+                if lossless {
+                    description.push(TodoSegment::Meta(key.to_string(), val.to_string()));
+                }
             } else if !part.is_empty() && !part.chars().all(|ch| ch.is_whitespace()) {
                 if let Some(context) = part.strip_prefix('@') {
                     description.push(TodoSegment::Tag(TodoTag::Context(context.to_string())));
@@ -462,42 +793,63 @@ impl FromStr for Todo {
                 } else {
                     description.push(TodoSegment::String(part.to_string()));
                 }
+            } else if lossless {
+                description.push(TodoSegment::String(part.to_string()));
             }
         }
 
-        if let Some(date) = todo.metadata.get(&"due".to_string()) {
-            if date == "today" {
-                if let Some(created) = todo.creation {
-                    todo.deadline = TodoDate::Day(created);
-                } else {
-                    todo.deadline = TodoDate::Day(Local::now().naive_local());
+        // Relative dates count from the creation date, so that they mean the
+        // same thing whenever the todo is read.
+        let today = todo
+            .creation
+            .or(now)
+            .map_or_else(|| SystemClock.today(), |now| now.date());
+        let resolve = |key: &str| -> Result<Option<NaiveDateTime>, TodoParseError> {
+            let Some(raw) = todo.metadata.get(&key.to_string()) else {
+                return Ok(None);
+            };
+
+            match raw.parse::<RelativeDate>() {
+                Ok(date) => {
+                    let date = date.resolve(today).ok_or(TodoParseError::BadDate)?;
+                    Ok(Some(date.and_time(NaiveTime::MIN)))
                 }
+                // A malformed `due:Nd` has always been an error, while other
+                // values are kept as metadata.
+                Err(e) if key == "due" && raw.ends_with('d') => Err(e),
+                Err(_) => Ok(None),
+            }
+        };
 
-                todo.metadata.remove(&"due".to_string());
-            } else if let Some(offset) = date.strip_suffix('d') {
-                let today = if let Some(created) = todo.creation {
-                    created
-                } else {
-                    Local::now().naive_local()
-                };
-
-                let offset = offset.parse::<u32>().map_err(|_| TodoParseError::BadDate)?;
-
-                let day = today.ordinal0() + offset;
-                let year = today.year() + day as i32 / 366;
-                todo.deadline = TodoDate::Day(
-                    today
-                        .with_year(year)
-                        .ok_or(TodoParseError::BadDate)?
-                        .with_day(day % 366)
-                        .ok_or(TodoParseError::BadDate)?,
-                );
-
-                todo.metadata.remove(&"due".to_string());
-            } else if let Ok(date) = NaiveDate::parse_from_str(date, "%F") {
-                todo.deadline =
-                    TodoDate::Day(date.and_hms_opt(0, 0, 0).ok_or(TodoParseError::BadDate)?);
-                todo.metadata.remove(&"due".to_string());
+        let timed = todo
+            .metadata
+            .get(&"due".to_string())
+            .and_then(|raw| due::parse_time(raw));
+        let deadline = match timed {
+            Some(date) => Some(date),
+            None => resolve("due")?.map(TodoDate::Day),
+        };
+        let threshold = resolve("t")?;
+
+        if let Some(date) = deadline {
+            todo.deadline = date;
+            todo.metadata.remove(&"due".to_string());
+        }
+
+        if let Some(date) = threshold {
+            todo.threshold = Some(date);
+            todo.metadata.remove(&"t".to_string());
+        }
+
+        if lossless {
+            for seg in description.iter_mut() {
+                if let TodoSegment::Meta(key, raw) = seg {
+                    if key == "due" && todo.deadline.is_some() {
+                        *seg = TodoSegment::Due(std::mem::take(raw), todo.deadline.clone());
+                    } else if key == "t" && todo.threshold.is_some() {
+                        *seg = TodoSegment::Threshold(std::mem::take(raw), todo.threshold);
+                    }
+                }
             }
         }
 
@@ -506,3 +858,10 @@ impl FromStr for Todo {
         Ok(todo)
     }
 }
+
+/// Splits a comma-separated list of IDs.
+fn split_ids(ids: Option<&String>) -> Vec<&str> {
+    ids.map_or(Vec::new(), |ids| {
+        ids.split(',').filter(|id| !id.is_empty()).collect()
+    })
+}
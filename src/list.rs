@@ -0,0 +1,248 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+use crate::archive::ArchiveOptions;
+use crate::clock::{Clock, SystemClock};
+use crate::error::LineError;
+use crate::query::Query;
+use crate::{Todo, TodoTable};
+
+/// A single line of a todo.txt file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TodoLine {
+    /// A successfully parsed todo.
+    Todo(Todo),
+    /// An empty (or whitespace-only) line.
+    Blank(String),
+    /// A line starting with `#`, stored verbatim.
+    Comment(String),
+    /// A line that failed to parse, stored verbatim.
+    Invalid(String),
+}
+
+impl Display for TodoLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Todo(todo) => {
+                let todo = todo.to_string();
+                if todo.trim().is_empty() || todo.starts_with('#') {
+                    write!(f, "{}", TodoTable::TODO_HEADER)?;
+                }
+                write!(f, "{todo}")
+            }
+            Self::Blank(s) | Self::Comment(s) | Self::Invalid(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// A whole todo.txt file.
+///
+/// Keeps line order, blank lines and comments, so that a file can be
+/// loaded, modified and saved again without losing anything. Todos are
+/// parsed with `Todo::parse_lossless`, so untouched lines are written back
+/// exactly as they were read. Lines that fail to parse are kept verbatim
+/// and reported in `errors`.
+///
+/// Like in `TodoTable`'s format, todos which would read as a blank line
+/// or a comment are written after a `# todo: ` marker, which is stripped
+/// again when parsing.
+///
+/// The line ending of the first line, `\n` or `\r\n`, is used for every
+/// line, and a missing newline at the end of the file stays missing.
+///
+/// Example:
+/// ```
+/// use todo_lib::prelude::*;
+///
+/// let text = "(A) Call mom +family\n\n# errands\nBuy milk @store\n";
+/// let mut list = TodoList::parse(text);
+///
+/// assert!(list.errors().is_empty());
+/// assert_eq!(list.iter().count(), 2);
+///
+/// list.add(Todo::from_str("Water plants").unwrap());
+/// assert_eq!(list.to_string(), format!("{text}Water plants\n"));
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TodoList {
+    lines: Vec<TodoLine>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    errors: Vec<LineError>,
+    /// Whether lines end in `\r\n` rather than `\n`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    crlf: bool,
+    /// Whether the last line has no line ending.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    no_final_newline: bool,
+}
+
+impl TodoList {
+    /// Returns an empty TodoList.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a todo.txt document.
+    ///
+    /// Never fails; lines that can't be parsed are kept as
+    /// `TodoLine::Invalid` and recorded in `errors`.
+    pub fn parse<S: AsRef<str>>(text: S) -> Self {
+        let text = text.as_ref();
+        let mut list = Self::new();
+        list.crlf = text
+            .split_once('\n')
+            .is_some_and(|(first, _)| first.ends_with('\r'));
+        list.no_final_newline = !text.is_empty() && !text.ends_with('\n');
+
+        for (i, line) in text.lines().enumerate() {
+            let todo = if let Some(todo) = line.strip_prefix(TodoTable::TODO_HEADER) {
+                todo
+            } else if line.trim().is_empty() {
+                list.lines.push(TodoLine::Blank(line.to_string()));
+                continue;
+            } else if line.starts_with('#') {
+                list.lines.push(TodoLine::Comment(line.to_string()));
+                continue;
+            } else {
+                line
+            };
+
+            match Todo::parse_lossless(todo) {
+                Ok(todo) => list.lines.push(TodoLine::Todo(todo)),
+                Err(error) => {
+                    list.errors.push(LineError { line: i + 1, error });
+                    list.lines.push(TodoLine::Invalid(line.to_string()));
+                }
+            }
+        }
+
+        list
+    }
+
+    /// Reads a todo.txt document from a reader.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Ok(Self::parse(text))
+    }
+
+    /// Reads a todo.txt file from a path.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(File::open(path)?)
+    }
+
+    /// Writes the document to a writer, one line per entry.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "{self}")?;
+        writer.flush()
+    }
+
+    /// Writes the document to a file, creating or truncating it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    /// Returns the lines of the document.
+    pub fn lines(&self) -> &[TodoLine] {
+        &self.lines
+    }
+
+    /// Returns the lines of the document, to edit them in place.
+    pub fn lines_mut(&mut self) -> &mut Vec<TodoLine> {
+        &mut self.lines
+    }
+
+    /// Returns the errors encountered while parsing.
+    pub fn errors(&self) -> &[LineError] {
+        &self.errors
+    }
+
+    /// Appends a todo to the end of the document.
+    pub fn add(&mut self, todo: Todo) {
+        self.lines.push(TodoLine::Todo(todo));
+    }
+
+    /// Returns an iterator over the todos, skipping other lines.
+    pub fn iter(&self) -> impl Iterator<Item = &Todo> {
+        self.lines.iter().filter_map(|line| match line {
+            TodoLine::Todo(todo) => Some(todo),
+            _ => None,
+        })
+    }
+
+    /// Moves completed todos to the end of another list, typically loaded
+    /// from `done.txt`. Other lines are left untouched.
+    ///
+    /// Returns how many todos were archived.
+    pub fn archive(&mut self, done: &mut TodoList, options: ArchiveOptions) -> usize {
+        self.archive_at(done, options, SystemClock.now())
+    }
+
+    /// Moves completed todos to the end of another list, as of the given
+    /// time.
+    pub fn archive_at(
+        &mut self,
+        done: &mut TodoList,
+        options: ArchiveOptions,
+        now: NaiveDateTime,
+    ) -> usize {
+        let mut archived = Vec::new();
+
+        for line in std::mem::take(&mut self.lines) {
+            match line {
+                TodoLine::Todo(todo) if options.should_archive(&todo, now) => archived.push(todo),
+                line => self.lines.push(line),
+            }
+        }
+
+        options.order(&mut archived);
+
+        let count = archived.len();
+        done.lines.extend(archived.into_iter().map(TodoLine::Todo));
+        count
+    }
+
+    /// Returns all the todos matching a query.
+    pub fn query(&self, query: &Query) -> Vec<&Todo> {
+        query.filter(self.iter())
+    }
+
+    /// Returns a mutable iterator over the todos, skipping other lines.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Todo> {
+        self.lines.iter_mut().filter_map(|line| match line {
+            TodoLine::Todo(todo) => Some(todo),
+            _ => None,
+        })
+    }
+}
+
+impl Display for TodoList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ending = if self.crlf { "\r\n" } else { "\n" };
+        for (i, line) in self.lines.iter().enumerate() {
+            write!(f, "{line}")?;
+
+            if i + 1 < self.lines.len() || !self.no_final_newline {
+                write!(f, "{ending}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromIterator<Todo> for TodoList {
+    fn from_iter<T: IntoIterator<Item = Todo>>(iter: T) -> Self {
+        Self {
+            lines: iter.into_iter().map(TodoLine::Todo).collect(),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Markdown checklists.
+//!
+//! A table is written as a level 1 heading with its title, followed by a
+//! level 2 heading per column, each with a GitHub-style checklist of its
+//! todos:
+//!
+//! ```markdown
+//! # Todos
+//!
+//! ## Work
+//!
+//! - [ ] (A) Review documents +report due:2024-01-31
+//! - [x] 2024-01-02 Clean desk
+//! ```
+//!
+//! Each item is a todo.txt line, with the `x ` of completed todos replaced
+//! by the checkbox, which alone decides whether a todo is completed. An
+//! open todo whose text starts with `x ` is written as `\x ` so it doesn't
+//! read as completed, and a leading `\` is doubled; one leading `\` is
+//! removed when parsing. Subtasks are indented under their parents.
+//!
+//! When parsing, any other text is ignored: paragraphs, list items without
+//! a checkbox, deeper headings and fenced code blocks. `*` and `+` list
+//! markers, and `[X]`, are accepted too. Items which fail to parse are
+//! kept as plain text and reported in `TodoTable::errors`.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::markdown;
+//!
+//! let text = "# Notes\n\nSome text.\n\n## Work\n\n- [ ] (A) Write report +q1 due:2024-01-31\n- [x] Send invoice\n";
+//! let table = markdown::parse_markdown(text).unwrap();
+//!
+//! assert_eq!(table.title(), "Notes");
+//! assert_eq!(table.columns()[0].todos.len(), 2);
+//! assert!(table.columns()[0].todos[1].completed);
+//!
+//! assert_eq!(
+//!     markdown::table_to_markdown(&table),
+//!     "# Notes\n\n## Work\n\n- [ ] (A) Write report +q1 due:2024-01-31\n- [x] Send invoice\n",
+//! );
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::error::{LineError, TableParseError};
+use crate::render::{Element, Renderer};
+use crate::{Todo, TodoColumn, TodoDescription, TodoSegment, TodoTable};
+
+/// Renders todos as checklist items, columns as level 2 headings and
+/// tables as level 1 headings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn element(&self, _: &Todo, _: Element, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn todo(&self, todo: &Todo, elements: Vec<(Element, String)>) -> String {
+        let elements: Vec<String> = elements
+            .into_iter()
+            .filter(|(element, _)| *element != Element::Tick)
+            .map(|(_, text)| text)
+            .collect();
+
+        let text = elements.join(" ");
+        let checkbox = if todo.completed { "[x]" } else { "[ ]" };
+        let escape = text.starts_with('\\') || (!todo.completed && text.starts_with("x "));
+        let escape = if escape { "\\" } else { "" };
+        format!("- {checkbox} {escape}{text}")
+    }
+
+    fn column(&self, col: &TodoColumn, todos: Vec<(usize, String)>) -> String {
+        let mut s = format!("## {}\n\n", col.title);
+        for (depth, todo) in todos {
+            s.push_str(&format!("{}{todo}\n", "  ".repeat(depth)));
+        }
+
+        s
+    }
+
+    fn table(&self, table: &TodoTable, columns: Vec<String>) -> String {
+        let mut s = format!("# {}\n", table.title());
+        for col in columns {
+            s.push('\n');
+            s.push_str(&col);
+        }
+
+        s
+    }
+}
+
+/// Returns a column as a level 2 heading followed by a checklist.
+pub fn column_to_markdown(col: &TodoColumn) -> String {
+    col.render(&Markdown)
+}
+
+/// Returns a table as a level 1 heading followed by its columns.
+pub fn table_to_markdown(table: &TodoTable) -> String {
+    table.render(&Markdown)
+}
+
+/// Parses a Markdown document into a table.
+///
+/// The first level 1 heading is the title; without one, the table gets the
+/// default title. Only fails if a todo comes before any column; items
+/// which fail to parse are reported in `errors`.
+pub fn parse_markdown<S: AsRef<str>>(text: S) -> Result<TodoTable, TableParseError> {
+    let mut table = TodoTable::new::<String>(None);
+    let mut titled = false;
+    let mut fenced = false;
+
+    for (i, line) in text.as_ref().lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        } else if fenced {
+            continue;
+        } else if let Some(title) = line.strip_prefix("# ") {
+            if !titled {
+                table = TodoTable::new(Some(title.trim()));
+                titled = true;
+            }
+        } else if let Some(title) = line.strip_prefix("## ") {
+            table.add_col(title.trim());
+        } else if let Some((checked, text)) = parse_item(trimmed) {
+            // Parsing the text as completed keeps a leading `x` in it as
+            // part of the description; the checkbox decides completion.
+            let text = text.strip_prefix('\\').unwrap_or(text);
+            let mut todo = match Todo::parse_lossless(format!("x {text}")) {
+                Ok(todo) => todo,
+                Err(error) => {
+                    table.errors.push(LineError { line: i + 1, error });
+                    Todo {
+                        description: TodoDescription(vec![TodoSegment::String(text.to_string())]),
+                        ..Todo::default()
+                    }
+                }
+            };
+            todo.completed = checked;
+            table
+                .columns_mut()
+                .last_mut()
+                .ok_or(TableParseError::MissingColumn(i + 1))?
+                .add(todo);
+        }
+    }
+
+    Ok(table)
+}
+
+/// Reads a Markdown document into a table.
+///
+/// Parse errors are returned as `io::ErrorKind::InvalidData`.
+pub fn read<R: Read>(mut reader: R) -> io::Result<TodoTable> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_markdown(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Reads a Markdown file into a table.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<TodoTable> {
+    read(File::open(path)?)
+}
+
+/// Writes a table as Markdown.
+pub fn write<W: Write>(mut writer: W, table: &TodoTable) -> io::Result<()> {
+    writer.write_all(table_to_markdown(table).as_bytes())?;
+    writer.flush()
+}
+
+/// Writes a table as Markdown to a file, creating or truncating it.
+pub fn save<P: AsRef<Path>>(path: P, table: &TodoTable) -> io::Result<()> {
+    write(BufWriter::new(File::create(path)?), table)
+}
+
+/// Returns whether a checklist item is checked, and its text.
+fn parse_item(line: &str) -> Option<(bool, &str)> {
+    let line = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?;
+
+    let (checked, text) = if let Some(text) = line.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = line
+        .strip_prefix("[x]")
+        .or_else(|| line.strip_prefix("[X]"))
+    {
+        (true, text)
+    } else {
+        return None;
+    };
+
+    match text.strip_prefix(' ') {
+        Some(text) => Some((checked, text)),
+        None if text.is_empty() => Some((checked, text)),
+        None => None,
+    }
+}
@@ -0,0 +1,461 @@
+//! Emacs Org-mode outlines.
+//!
+//! A table is written with its title in `#+TITLE:`, and a level 1 heading
+//! per column. Todos are headings below their column, and subtasks are
+//! nested below their parents:
+//!
+//! ```org
+//! #+TITLE: Todos
+//! * Work
+//! ** TODO [#A] Review documents :report:@office:
+//! DEADLINE: <2024-01-31 Wed> SCHEDULED: <2024-01-20 Sat>
+//! :PROPERTIES:
+//! :CREATED: [2024-01-01 Mon]
+//! :id: 1
+//! :END:
+//! *** DONE Print them
+//! CLOSED: [2024-01-02 Tue]
+//! ```
+//!
+//! Todos map onto headings as follows:
+//!
+//! - `completed`: the `TODO` or `DONE` keyword
+//! - `priority`: the `[#A]` cookie
+//! - `deadline`, `threshold` and `completion_date`: the `DEADLINE`,
+//!   `SCHEDULED` and `CLOSED` timestamps
+//! - `creation`: the `CREATED` property
+//! - tags: `:@context:` and `:project:` heading tags; tags with characters
+//!   Org doesn't allow in tags, and projects starting with `@`, stay in the
+//!   heading text
+//! - metadata: properties, except for `parent`, which is implied by the
+//!   nesting
+//!
+//! `TodoDate::Always` has no Org equivalent, and isn't exported. Org
+//! timestamps have no UTC offset either, so a deadline with an offset is
+//! exported in local time, and read back without one.
+//!
+//! When parsing, every heading below a column is a todo, with or without a
+//! keyword. A todo whose parent has no `id:` tag is given one. Body text,
+//! other keywords and property values which can't be written as todo.txt
+//! metadata are ignored.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::org;
+//!
+//! let text = "* Work\n** TODO [#B] Write report :q1:@desk:\nDEADLINE: <2024-01-31 Wed>\n";
+//! let table = org::parse_org(text).unwrap();
+//!
+//! let todo = &table.columns()[0].todos[0];
+//! assert_eq!(todo.to_string(), "(B) Write report +q1 @desk due:2024-01-31");
+//!
+//! assert_eq!(org::table_to_org(&table), format!("#+TITLE: Todos\n{text}"));
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::error::{LineError, TableParseError, TodoParseError};
+use crate::helper::is_meta_value;
+use crate::table::next_id;
+use crate::{
+    Todo, TodoColumn, TodoDate, TodoDescription, TodoPriority, TodoSegment, TodoTable, TodoTag,
+};
+
+const TITLE: &str = "#+TITLE:";
+
+/// Returns a column as a level 1 heading followed by its todos.
+pub fn column_to_org(col: &TodoColumn) -> String {
+    let mut s = format!("* {}\n", col.title);
+    for (depth, todo) in col.tree() {
+        s.push_str(&entry(todo, depth + 2));
+    }
+
+    s
+}
+
+/// Returns a table as Org, with a level 1 heading per column.
+pub fn table_to_org(table: &TodoTable) -> String {
+    let mut s = format!("{TITLE} {}\n", table.title());
+    for col in table.columns() {
+        s.push_str(&column_to_org(col));
+    }
+
+    s
+}
+
+/// Parses an Org outline into a table.
+pub fn parse_org<S: AsRef<str>>(text: S) -> Result<TodoTable, TableParseError> {
+    let bad_date = |line: usize| {
+        TableParseError::Todo(LineError {
+            line,
+            error: TodoParseError::BadDate,
+        })
+    };
+
+    let mut title = None;
+    // Each todo along with its heading level.
+    let mut columns: Vec<(String, Vec<(usize, Todo)>)> = Vec::new();
+    let mut in_todo = false;
+    let mut in_drawer = false;
+
+    for (i, line) in text.as_ref().lines().enumerate() {
+        if let Some((level, text)) = heading(line) {
+            in_drawer = false;
+            in_todo = level > 1;
+
+            if level == 1 {
+                columns.push((text.trim().to_string(), Vec::new()));
+            } else {
+                columns
+                    .last_mut()
+                    .ok_or(TableParseError::MissingColumn(i + 1))?
+                    .1
+                    .push((level, parse_headline(text)));
+            }
+            continue;
+        }
+
+        if line
+            .get(..TITLE.len())
+            .is_some_and(|p| p.eq_ignore_ascii_case(TITLE))
+        {
+            title.get_or_insert_with(|| line[TITLE.len()..].trim().to_string());
+            continue;
+        }
+
+        let Some((_, todo)) = columns
+            .last_mut()
+            .and_then(|(_, todos)| todos.last_mut())
+            .filter(|_| in_todo)
+        else {
+            continue;
+        };
+
+        let trimmed = line.trim();
+        if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+            in_drawer = true;
+        } else if trimmed.eq_ignore_ascii_case(":END:") {
+            in_drawer = false;
+        } else if in_drawer {
+            let Some((key, value)) = trimmed
+                .strip_prefix(':')
+                .and_then(|prop| prop.split_once(':'))
+            else {
+                continue;
+            };
+
+            let value = value.trim();
+            if key.eq_ignore_ascii_case("CREATED") {
+                todo.creation = Some(parse_timestamp(value).ok_or_else(|| bad_date(i + 1))?);
+            } else if is_meta_value(key) && is_meta_value(value) {
+                todo.set_meta(key, value);
+            }
+        } else {
+            for (keyword, timestamp) in planning(trimmed) {
+                let (date, time) = timestamp_parts(timestamp).ok_or_else(|| bad_date(i + 1))?;
+                let date = date.and_time(time.unwrap_or(NaiveTime::MIN));
+                match keyword {
+                    "DEADLINE:" if time.is_some() => todo.deadline = TodoDate::Timed(date),
+                    "DEADLINE:" => todo.deadline = TodoDate::Day(date),
+                    "SCHEDULED:" => todo.threshold = Some(date),
+                    _ => todo.completion_date = Some(date),
+                }
+            }
+        }
+    }
+
+    nest(&mut columns);
+
+    let mut table = TodoTable::new(title);
+    for (title, todos) in columns {
+        table.add_col(title);
+        let col = table
+            .columns_mut()
+            .last_mut()
+            .expect("column was just added");
+        for (_, todo) in todos {
+            col.add(todo);
+        }
+    }
+
+    Ok(table)
+}
+
+/// Reads an Org outline into a table.
+///
+/// Parse errors are returned as `io::ErrorKind::InvalidData`.
+pub fn read<R: Read>(mut reader: R) -> io::Result<TodoTable> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_org(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Reads an Org file into a table.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<TodoTable> {
+    read(File::open(path)?)
+}
+
+/// Writes a table as Org.
+pub fn write<W: Write>(mut writer: W, table: &TodoTable) -> io::Result<()> {
+    writer.write_all(table_to_org(table).as_bytes())?;
+    writer.flush()
+}
+
+/// Writes a table as Org to a file, creating or truncating it.
+pub fn save<P: AsRef<Path>>(path: P, table: &TodoTable) -> io::Result<()> {
+    write(BufWriter::new(File::create(path)?), table)
+}
+
+/// Returns a todo as a heading of the given level, with its planning line
+/// and properties.
+fn entry(todo: &Todo, level: usize) -> String {
+    let mut s = "*".repeat(level);
+    s.push_str(if todo.completed { " DONE" } else { " TODO" });
+    if todo.priority.is_some() {
+        s.push_str(&format!(" [#{}]", todo.priority as u8 as char));
+    }
+
+    let mut text = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    for seg in &todo.description.0 {
+        match seg {
+            TodoSegment::Tag(tag) if is_tag(tag) => {
+                let tag = match tag {
+                    TodoTag::Project(p) => p.clone(),
+                    TodoTag::Context(c) => format!("@{c}"),
+                };
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            seg if !seg.is_inline_meta() => text.push(seg.clone()),
+            _ => {}
+        }
+    }
+
+    let text = TodoDescription(text).to_string();
+    if !text.is_empty() {
+        s.push_str(&format!(" {text}"));
+    }
+    if !tags.is_empty() {
+        s.push_str(&format!(" :{}:", tags.join(":")));
+    }
+    s.push('\n');
+
+    let mut planning = Vec::new();
+    let deadline = match todo.deadline {
+        TodoDate::Day(date) => Some(date.format("%Y-%m-%d %a").to_string()),
+        ref deadline => deadline
+            .local()
+            .map(|date| date.format("%Y-%m-%d %a %H:%M").to_string()),
+    };
+    if let Some(deadline) = deadline {
+        planning.push(format!("DEADLINE: <{deadline}>"));
+    }
+    if let Some(date) = todo.threshold {
+        planning.push(format!("SCHEDULED: <{}>", format_timestamp(date)));
+    }
+    if let Some(date) = todo.completion_date.filter(|_| todo.completed) {
+        planning.push(format!("CLOSED: [{}]", format_timestamp(date)));
+    }
+    if !planning.is_empty() {
+        s.push_str(&format!("{}\n", planning.join(" ")));
+    }
+
+    let mut properties = Vec::new();
+    if let Some(date) = todo.creation {
+        properties.push(format!(":CREATED: [{}]", format_timestamp(date)));
+    }
+    for (key, val) in &todo.metadata.data {
+        if key != "parent" || level == 2 {
+            properties.push(format!(":{key}: {val}"));
+        }
+    }
+    if !properties.is_empty() {
+        s.push_str(&format!(":PROPERTIES:\n{}\n:END:\n", properties.join("\n")));
+    }
+
+    s
+}
+
+/// Builds a todo from the text of its heading.
+fn parse_headline(text: &str) -> Todo {
+    let mut text = text.trim();
+
+    fn keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+        let rest = text.strip_prefix(keyword)?;
+        (rest.is_empty() || rest.starts_with(' ')).then(|| rest.trim_start())
+    }
+
+    let mut completed = false;
+    if let Some(rest) = keyword(text, "TODO") {
+        text = rest;
+    } else if let Some(rest) = keyword(text, "DONE") {
+        text = rest;
+        completed = true;
+    }
+
+    let mut priority = TodoPriority::None;
+    if let Some(p) = text
+        .strip_prefix("[#")
+        .and_then(|rest| rest.get(..2)?.strip_suffix(']'))
+        .and_then(|p| TodoPriority::try_from(format!("({p})").as_str()).ok())
+    {
+        priority = p;
+        text = text[4..].trim_start();
+    }
+
+    let (text, tags) = match text.rsplit_once(' ') {
+        Some((head, tags)) if is_tag_list(tags) => (head.trim_end(), tags),
+        None if is_tag_list(text) => ("", text),
+        _ => (text, ""),
+    };
+
+    let mut todo = Todo::new(text, TodoDate::Never, priority);
+    todo.creation = None;
+    todo.completed = completed;
+
+    for tag in tags.split(':').filter(|tag| !tag.is_empty()) {
+        let tag = match tag.strip_prefix('@') {
+            Some(context) => TodoTag::context(context),
+            None => TodoTag::project(tag),
+        };
+
+        if let Some(tag) = tag.filter(|tag| !todo.has_tag(tag.clone())) {
+            todo.description.0.push(TodoSegment::Tag(tag));
+        }
+    }
+
+    todo
+}
+
+/// Sets the `parent:` tag of nested todos, giving parents IDs as needed.
+fn nest(columns: &mut [(String, Vec<(usize, Todo)>)]) {
+    let mut parents = Vec::new();
+    for (c, (_, todos)) in columns.iter().enumerate() {
+        // The indices of the enclosing headings.
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, (level, _)) in todos.iter().enumerate() {
+            while stack.last().is_some_and(|&p| todos[p].0 >= *level) {
+                stack.pop();
+            }
+            if let Some(&p) = stack.last() {
+                parents.push((c, p, i));
+            }
+            stack.push(i);
+        }
+    }
+
+    for (c, p, i) in parents {
+        let id = match columns[c].1[p].1.id() {
+            Some(id) => id.clone(),
+            None => {
+                let id = next_id(
+                    columns
+                        .iter()
+                        .flat_map(|(_, todos)| todos.iter().map(|(_, t)| t)),
+                );
+                columns[c].1[p].1.set_id(id.clone());
+                id
+            }
+        };
+
+        columns[c].1[i].1.set_parent(id);
+    }
+}
+
+/// Returns the level and text of a heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&ch| ch == '*').count();
+    let rest = &line[level..];
+    (level > 0 && (rest.is_empty() || rest.starts_with(' '))).then(|| (level, rest.trim_start()))
+}
+
+/// Returns the keywords and timestamps of a planning line.
+fn planning(line: &str) -> Vec<(&'static str, &str)> {
+    const KEYWORDS: [&str; 3] = ["DEADLINE:", "SCHEDULED:", "CLOSED:"];
+
+    if !KEYWORDS.iter().any(|kw| line.starts_with(kw)) {
+        return Vec::new();
+    }
+
+    let mut found = Vec::new();
+    for keyword in KEYWORDS {
+        let Some(start) = line.find(keyword) else {
+            continue;
+        };
+
+        let rest = line[start + keyword.len()..].trim_start();
+        let close = match rest.chars().next() {
+            Some('<') => '>',
+            Some('[') => ']',
+            _ => continue,
+        };
+        if let Some(end) = rest.find(close) {
+            found.push((keyword, &rest[..=end]));
+        }
+    }
+
+    found
+}
+
+/// Formats a date as the inside of an Org timestamp.
+fn format_timestamp(date: NaiveDateTime) -> String {
+    if date.time() == NaiveTime::MIN {
+        date.format("%Y-%m-%d %a").to_string()
+    } else {
+        date.format("%Y-%m-%d %a %H:%M").to_string()
+    }
+}
+
+/// Parses an Org timestamp, such as `<2024-01-31 Wed 10:00 +1w>`, at
+/// midnight if it has no time.
+fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    let (date, time) = timestamp_parts(timestamp)?;
+    Some(date.and_time(time.unwrap_or(NaiveTime::MIN)))
+}
+
+/// Splits an Org timestamp into its date and time, if it has one.
+///
+/// Day names and repeaters are ignored.
+fn timestamp_parts(timestamp: &str) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    let inner = timestamp
+        .strip_prefix(['<', '['])?
+        .strip_suffix(['>', ']'])?;
+
+    let mut parts = inner.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%F").ok()?;
+    let time = parts.find_map(|part| NaiveTime::parse_from_str(part, "%H:%M").ok());
+
+    Some((date, time))
+}
+
+/// Returns true if the tag can be written as an Org tag. Projects starting
+/// with `@` can't, since they'd read as contexts.
+fn is_tag(tag: &TodoTag) -> bool {
+    match tag {
+        TodoTag::Project(p) => is_tag_name(p) && !p.starts_with('@'),
+        TodoTag::Context(c) => is_tag_name(c),
+    }
+}
+
+/// Returns true if the text is an Org tag list, such as `:work:@home:`.
+fn is_tag_list(s: &str) -> bool {
+    s.len() > 2
+        && s.starts_with(':')
+        && s.ends_with(':')
+        && s[1..s.len() - 1].split(':').all(is_tag_name)
+}
+
+/// Returns true if the text only contains characters Org allows in tags.
+fn is_tag_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || "_@#%".contains(ch))
+}
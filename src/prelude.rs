@@ -1,9 +1,14 @@
 pub use std::str::FromStr;
 
 pub use crate::{
-    due::TodoDate,
+    clock::Clock,
+    due::{DuePolicy, TodoDate},
     helper::IsDue,
+    list::{TodoLine, TodoList},
     priority::TodoPriority,
+    recur::Recurrence,
+    relative::RelativeDate,
+    render::Renderer,
     table::{TodoColumn, TodoTable},
     Todo, TodoTag,
 };
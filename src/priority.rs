@@ -3,6 +3,7 @@ use std::fmt::Display;
 use crate::error::InvalidPriorityError;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum TodoPriority {
     #[default]
@@ -0,0 +1,411 @@
+//! A small filter language over todos.
+//!
+//! A query is made of terms combined with `and`, `or`, `not` and
+//! parentheses. Adjacent terms are implicitly joined with `and`.
+//!
+//! Terms:
+//! - `+project` / `@context`: has the tag
+//! - `done`: is complete
+//! - `due`: is due (see `IsDue`)
+//! - `hidden`: is hidden by its threshold date
+//! - `key:value`: has the metadata pair; `key:*` matches any value
+//! - `pri<op>X`: compares priority, where higher priorities are greater
+//!   (so `pri>=B` matches A and B)
+//! - `due<op>D`, `t<op>D`, `created<op>D`, `completed<op>D`: compares a date,
+//!   where `D` is `YYYY-MM-DD` or a date relative to today, such as
+//!   `today+7d`, `tomorrow` or `eom` (see the `relative` module)
+//! - anything else, or a `"quoted string"`: the description contains the
+//!   text, ignoring case
+//!
+//! Operators are `=`, `!=`, `<`, `<=`, `>` and `>=`. Date comparisons never
+//! match a todo which doesn't have that date.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::query::Query;
+//!
+//! let mut col = TodoColumn::new("Todo");
+//! col.add(Todo::from_str("(A) Call Bob +work @phone due:2000-01-01").unwrap());
+//! col.add(Todo::from_str("(C) Call Alice +work @phone").unwrap());
+//! col.add(Todo::from_str("x (A) Call Carol +work @phone").unwrap());
+//!
+//! let query = Query::parse("+work and @phone and pri>=B and due<today+7d and not done").unwrap();
+//! let found = col.query(&query);
+//!
+//! assert_eq!(found.len(), 1);
+//! assert!(found[0].to_string().contains("Bob"));
+//! ```
+
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::QueryError;
+use crate::helper::IsDue;
+use crate::{RelativeDate, Todo, TodoDate, TodoPriority};
+
+/// A comparison operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    const ALL: [(&'static str, Cmp); 6] = [
+        ("<=", Cmp::Le),
+        (">=", Cmp::Ge),
+        ("!=", Cmp::Ne),
+        ("<", Cmp::Lt),
+        (">", Cmp::Gt),
+        ("=", Cmp::Eq),
+    ];
+
+    fn test<T: PartialOrd>(&self, a: T, b: T) -> bool {
+        match self {
+            Cmp::Eq => a == b,
+            Cmp::Ne => a != b,
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Gt => a > b,
+            Cmp::Ge => a >= b,
+        }
+    }
+}
+
+/// A date field of a todo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateField {
+    Due,
+    Threshold,
+    Created,
+    Completed,
+}
+
+impl DateField {
+    const ALL: [(&'static str, DateField); 5] = [
+        ("due", DateField::Due),
+        ("t", DateField::Threshold),
+        ("threshold", DateField::Threshold),
+        ("created", DateField::Created),
+        ("completed", DateField::Completed),
+    ];
+
+    fn get(&self, todo: &Todo) -> Option<NaiveDate> {
+        match self {
+            DateField::Due => match todo.deadline {
+                TodoDate::Always => Some(NaiveDate::MIN),
+                ref deadline => deadline.local().map(|date| date.date()),
+            },
+            DateField::Threshold => todo.threshold.map(|date| date.date()),
+            DateField::Created => todo.creation.map(|date| date.date()),
+            DateField::Completed => todo.completion_date.map(|date| date.date()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Term {
+    Project(String),
+    Context(String),
+    Done,
+    Due,
+    Hidden,
+    Meta(String, Option<String>),
+    Priority(Cmp, TodoPriority),
+    Date(DateField, Cmp, RelativeDate),
+    Text(String),
+}
+
+impl Term {
+    fn parse(word: &str) -> Result<Self, QueryError> {
+        match word.to_lowercase().as_str() {
+            "done" => return Ok(Self::Done),
+            "due" => return Ok(Self::Due),
+            "hidden" => return Ok(Self::Hidden),
+            _ => {}
+        }
+
+        if let Some(project) = word.strip_prefix('+').filter(|s| !s.is_empty()) {
+            return Ok(Self::Project(project.to_string()));
+        }
+
+        if let Some(context) = word.strip_prefix('@').filter(|s| !s.is_empty()) {
+            return Ok(Self::Context(context.to_string()));
+        }
+
+        if let Some(rest) = word.strip_prefix("pri") {
+            if let Some((cmp, value)) = split_cmp(rest) {
+                let priority =
+                    TodoPriority::try_from(format!("({})", value.to_uppercase()).as_str())
+                        .map_err(|_| QueryError::BadPriority(value.to_string()))?;
+                return Ok(Self::Priority(cmp, priority));
+            }
+        }
+
+        for (name, field) in DateField::ALL {
+            if let Some((cmp, value)) = word.strip_prefix(name).and_then(split_cmp) {
+                let date = RelativeDate::from_str(value)
+                    .map_err(|_| QueryError::BadDate(value.to_string()))?;
+                return Ok(Self::Date(field, cmp, date));
+            }
+        }
+
+        if let Some((key, val)) = word.split_once(':') {
+            if !key.is_empty() && !val.is_empty() {
+                let val = (val != "*").then(|| val.to_string());
+                return Ok(Self::Meta(key.to_string(), val));
+            }
+        }
+
+        Ok(Self::Text(word.to_lowercase()))
+    }
+
+    fn matches(&self, todo: &Todo, now: NaiveDateTime) -> bool {
+        match self {
+            Self::Project(tag) => todo.has_project_tag(tag),
+            Self::Context(tag) => todo.has_context_tag(tag),
+            Self::Done => todo.completed,
+            Self::Due => todo.due_at(now),
+            Self::Hidden => todo.is_hidden_at(now),
+            Self::Meta(key, val) => match (todo.get_meta(key), val) {
+                (Some(found), Some(val)) => found == val,
+                (found, None) => found.is_some(),
+                (None, _) => false,
+            },
+            Self::Priority(cmp, priority) => cmp.test(rank(todo.priority), rank(*priority)),
+            Self::Date(field, cmp, value) => match (field.get(todo), value.resolve(now.date())) {
+                (Some(date), Some(value)) => cmp.test(date, value),
+                _ => false,
+            },
+            Self::Text(text) => todo.description.to_string().to_lowercase().contains(text),
+        }
+    }
+}
+
+/// Splits a leading comparison operator from its operand.
+fn split_cmp(s: &str) -> Option<(Cmp, &str)> {
+    Cmp::ALL
+        .iter()
+        .find_map(|(op, cmp)| s.strip_prefix(op).map(|rest| (*cmp, rest)))
+}
+
+/// Ranks a priority so that A is the highest and None the lowest.
+fn rank(priority: TodoPriority) -> u8 {
+    if priority.is_some() {
+        b'Z' + 1 - priority as u8
+    } else {
+        0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+impl Expr {
+    fn matches(&self, todo: &Todo, now: NaiveDateTime) -> bool {
+        match self {
+            Self::And(a, b) => a.matches(todo, now) && b.matches(todo, now),
+            Self::Or(a, b) => a.matches(todo, now) || b.matches(todo, now),
+            Self::Not(a) => !a.matches(todo, now),
+            Self::Term(term) => term.matches(todo, now),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    Quoted(String),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if ch == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else if ch == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(ch) => text.push(ch),
+                    None => return Err(QueryError::UnclosedQuote),
+                }
+            }
+            tokens.push(Token::Quoted(text));
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() || ch == '(' || ch == ')' {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.not()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.pos >= self.tokens.len()
+                || self.peek_keyword("or")
+                || self.tokens[self.pos] == Token::Close
+            {
+                break;
+            }
+
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, QueryError> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, QueryError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(QueryError::UnexpectedEnd)?;
+        self.pos += 1;
+
+        match token {
+            Token::Open => {
+                let expr = self.or()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    return Err(QueryError::UnclosedParen);
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Token::Close => Err(QueryError::UnexpectedToken(")".to_string())),
+            Token::Quoted(text) => Ok(Expr::Term(Term::Text(text.to_lowercase()))),
+            Token::Word(word) => {
+                if ["and", "or"].iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                    return Err(QueryError::UnexpectedToken(word));
+                }
+
+                Ok(Expr::Term(Term::parse(&word)?))
+            }
+        }
+    }
+}
+
+/// A compiled query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query(Expr);
+
+impl Query {
+    /// Compiles a query expression.
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            tokens: tokenize(s.as_ref())?,
+            pos: 0,
+        };
+
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(QueryError::UnexpectedToken(match token {
+                Token::Open => "(".to_string(),
+                Token::Close => ")".to_string(),
+                Token::Word(s) | Token::Quoted(s) => s.clone(),
+            }));
+        }
+
+        Ok(Self(expr))
+    }
+
+    /// Returns true if the todo matches the query.
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.matches_at(todo, SystemClock.now())
+    }
+
+    /// Returns true if the todo matches the query, with `today` and `due`
+    /// relative to `now`.
+    pub fn matches_at(&self, todo: &Todo, now: NaiveDateTime) -> bool {
+        self.0.matches(todo, now)
+    }
+
+    /// Returns all the todos which match the query.
+    pub fn filter<'a, I: IntoIterator<Item = &'a Todo>>(&self, todos: I) -> Vec<&'a Todo> {
+        self.filter_at(todos, SystemClock.now())
+    }
+
+    /// Returns all the todos which match the query at the given time.
+    pub fn filter_at<'a, I: IntoIterator<Item = &'a Todo>>(
+        &self,
+        todos: I,
+        now: NaiveDateTime,
+    ) -> Vec<&'a Todo> {
+        todos
+            .into_iter()
+            .filter(|todo| self.matches_at(todo, now))
+            .collect()
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, Weekday};
+
+use crate::error::TodoParseError;
+
+/// The unit of a `rec:` interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RecurUnit {
+    /// `d`: calendar days.
+    Day,
+    /// `b`: business days, skipping Saturdays and Sundays.
+    BusinessDay,
+    /// `w`: weeks.
+    Week,
+    /// `m`: calendar months.
+    Month,
+    /// `y`: calendar years.
+    Year,
+}
+
+impl RecurUnit {
+    fn suffix(&self) -> char {
+        match self {
+            Self::Day => 'd',
+            Self::BusinessDay => 'b',
+            Self::Week => 'w',
+            Self::Month => 'm',
+            Self::Year => 'y',
+        }
+    }
+}
+
+/// A recurrence, as given by the `rec:` metadata tag.
+///
+/// `rec:1w` recurs one week after the todo is completed, while the strict
+/// form `rec:+1w` recurs one week after its previous due date.
+///
+/// Example:
+/// ```
+/// use todo_lib::prelude::*;
+///
+/// let mut todo = Todo::from_str("2024-01-01 Water plants due:2024-01-03 rec:+1w").unwrap();
+/// let next = todo.complete_recurring().expect("todo should recur");
+///
+/// assert!(todo.completed);
+/// assert!(!next.completed);
+/// assert_eq!(next.deadline.to_string(), "due:2024-01-10");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Recurrence {
+    /// Recur from the previous due date instead of the completion date.
+    pub strict: bool,
+    pub amount: u32,
+    pub unit: RecurUnit,
+}
+
+impl Recurrence {
+    /// Returns the given date moved forward by the recurrence interval.
+    ///
+    /// Months and years are clamped to the end of the month, so that
+    /// `2024-01-31` plus one month is `2024-02-29`.
+    pub fn apply(&self, date: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self.unit {
+            RecurUnit::Day => date.checked_add_days(Days::new(self.amount as u64)),
+            RecurUnit::Week => date.checked_add_days(Days::new(self.amount as u64 * 7)),
+            RecurUnit::Month => date.checked_add_months(Months::new(self.amount)),
+            RecurUnit::Year => date.checked_add_months(Months::new(self.amount.checked_mul(12)?)),
+            RecurUnit::BusinessDay => add_business_days(date.date(), self.amount.into())
+                .map(|day| day.and_time(date.time())),
+        }
+    }
+}
+
+/// Moves a day by a signed number of business days, skipping Saturdays and
+/// Sundays.
+pub(crate) fn add_business_days(day: NaiveDate, amount: i64) -> Option<NaiveDate> {
+    let next_business_day = |mut day: NaiveDate| loop {
+        day = if amount < 0 {
+            day.pred_opt()?
+        } else {
+            day.succ_opt()?
+        };
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            return Some(day);
+        }
+    };
+
+    let n = amount.unsigned_abs();
+    if n == 0 {
+        return Some(day);
+    }
+
+    // From a business day, every five business days are a whole week.
+    let mut day = next_business_day(day)?;
+    let weeks = Days::new((n - 1) / 5 * 7);
+    day = if amount < 0 {
+        day.checked_sub_days(weeks)?
+    } else {
+        day.checked_add_days(weeks)?
+    };
+    for _ in 0..(n - 1) % 5 {
+        day = next_business_day(day)?;
+    }
+
+    Some(day)
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.strict {
+            write!(f, "+")?;
+        }
+
+        write!(f, "{}{}", self.amount, self.unit.suffix())
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = TodoParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (strict, s) = match s.strip_prefix('+') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+
+        let unit = match s.chars().last() {
+            Some('d') => RecurUnit::Day,
+            Some('b') => RecurUnit::BusinessDay,
+            Some('w') => RecurUnit::Week,
+            Some('m') => RecurUnit::Month,
+            Some('y') => RecurUnit::Year,
+            _ => return Err(TodoParseError::BadRecurrence),
+        };
+
+        let amount = s[..s.len() - 1]
+            .parse::<u32>()
+            .map_err(|_| TodoParseError::BadRecurrence)?;
+
+        Ok(Self {
+            strict,
+            amount,
+            unit,
+        })
+    }
+}
@@ -0,0 +1,231 @@
+//! Dates relative to a reference day, for `due:`, `t:` and queries.
+//!
+//! These forms are understood, ignoring case:
+//!
+//! - `YYYY-MM-DD`: that date
+//! - `today`, `tomorrow` and `yesterday`, or `tod` and `tom`
+//! - an offset, such as `3d`, `+2w`, `1m` or `-1y`: the reference day moved
+//!   by that many days, business days (`b`), weeks, months or years; `today`
+//!   may come first, as in `today+7d`
+//! - a weekday, such as `fri` or `friday`: the first such day after the
+//!   reference day
+//! - `next-` and a weekday, such as `next-mon`: that day of the following
+//!   week
+//! - `eow`, `eom` and `eoy`: the last day of the week, month or year
+//! - `next-week`, `next-month` and `next-year`: the first day of the
+//!   following week, month or year
+//!
+//! Weeks start on Monday. Months and years are clamped to the end of the
+//! month, so that `2024-01-31` plus one month is `2024-02-29`.
+//!
+//! When parsing a todo, relative dates resolve against its creation date,
+//! or today if it has none; see `Todo::from_str_at` to supply today.
+//! `Todo::parse_lossless` keeps the relative token as written; use
+//! `Todo::pin_relative_dates` to write the resolved date instead.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::chrono::NaiveDate;
+//!
+//! // A Wednesday.
+//! let today = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+//! let resolve = |s: &str| s.parse::<RelativeDate>().unwrap().resolve(today).unwrap();
+//!
+//! assert_eq!(resolve("tomorrow").to_string(), "2024-02-01");
+//! assert_eq!(resolve("fri").to_string(), "2024-02-02");
+//! assert_eq!(resolve("next-mon").to_string(), "2024-02-05");
+//! assert_eq!(resolve("+1m").to_string(), "2024-02-29");
+//! assert_eq!(resolve("eow").to_string(), "2024-02-04");
+//!
+//! let todo = Todo::from_str("2023-12-30 Pay rent due:3d").unwrap();
+//! assert_eq!(todo.deadline.to_string(), "due:2024-01-02");
+//! ```
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use crate::error::TodoParseError;
+use crate::recur::{add_business_days, RecurUnit, Recurrence};
+
+/// A span of the calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Period {
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+
+    /// Returns the first day of the period after the one containing `day`.
+    fn next(&self, day: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Week => {
+                day.checked_add_days(Days::new(7 - day.weekday().num_days_from_monday() as u64))
+            }
+            Self::Month => day.with_day(1)?.checked_add_months(Months::new(1)),
+            Self::Year => NaiveDate::from_ymd_opt(day.year().checked_add(1)?, 1, 1),
+        }
+    }
+}
+
+/// A date, possibly relative to a reference day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RelativeDate {
+    Fixed(NaiveDate),
+    /// The reference day, moved by a signed amount of the unit.
+    Offset(i64, RecurUnit),
+    /// The first such weekday after the reference day.
+    Weekday(Weekday),
+    /// That weekday of the week after the reference day's.
+    NextWeekday(Weekday),
+    /// The last day of the period containing the reference day.
+    EndOf(Period),
+    /// The first day of the period after the reference day's.
+    Next(Period),
+}
+
+impl RelativeDate {
+    /// Returns the date, relative to the given day.
+    ///
+    /// Returns `None` if the date is out of range.
+    pub fn resolve(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Self::Fixed(date) => Some(date),
+            Self::Offset(amount, unit) => offset(today, amount, unit),
+            Self::Weekday(weekday) => {
+                let days = (weekday.num_days_from_monday() + 6
+                    - today.weekday().num_days_from_monday())
+                    % 7
+                    + 1;
+                today.checked_add_days(Days::new(days as u64))
+            }
+            Self::NextWeekday(weekday) => Period::Week
+                .next(today)?
+                .checked_add_days(Days::new(weekday.num_days_from_monday() as u64)),
+            Self::EndOf(period) => period.next(today)?.pred_opt(),
+            Self::Next(period) => period.next(today),
+        }
+    }
+
+    /// Returns true unless the date is a fixed date.
+    pub fn is_relative(&self) -> bool {
+        !matches!(self, Self::Fixed(_))
+    }
+}
+
+/// Moves a day by a signed amount of a unit.
+fn offset(day: NaiveDate, amount: i64, unit: RecurUnit) -> Option<NaiveDate> {
+    let n = amount.unsigned_abs();
+    if amount >= 0 {
+        let rec = Recurrence {
+            strict: false,
+            amount: n.try_into().ok()?,
+            unit,
+        };
+        return rec
+            .apply(day.and_time(chrono::NaiveTime::MIN))
+            .map(|date| date.date());
+    }
+
+    match unit {
+        RecurUnit::Day => day.checked_sub_days(Days::new(n)),
+        RecurUnit::Week => day.checked_sub_days(Days::new(n.checked_mul(7)?)),
+        RecurUnit::Month => day.checked_sub_months(Months::new(n.try_into().ok()?)),
+        RecurUnit::Year => day.checked_sub_months(Months::new(n.checked_mul(12)?.try_into().ok()?)),
+        RecurUnit::BusinessDay => add_business_days(day, amount),
+    }
+}
+
+/// Parses a signed offset such as `+2w`, `-1m` or `3d`.
+fn parse_offset(s: &str) -> Option<(i64, RecurUnit)> {
+    let (sign, s) = match s.chars().next()? {
+        '+' => (1, &s[1..]),
+        '-' => (-1, &s[1..]),
+        _ => (1, s),
+    };
+
+    let rec: Recurrence = s.parse().ok()?;
+    if rec.strict {
+        return None;
+    }
+
+    Some((sign * rec.amount as i64, rec.unit))
+}
+
+impl FromStr for RelativeDate {
+    type Err = TodoParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%F") {
+            return Ok(Self::Fixed(date));
+        }
+
+        let s = s.to_lowercase();
+        let date = match s.as_str() {
+            "today" | "tod" => Self::Offset(0, RecurUnit::Day),
+            "tomorrow" | "tom" => Self::Offset(1, RecurUnit::Day),
+            "yesterday" => Self::Offset(-1, RecurUnit::Day),
+            "eow" => Self::EndOf(Period::Week),
+            "eom" => Self::EndOf(Period::Month),
+            "eoy" => Self::EndOf(Period::Year),
+            "next-week" => Self::Next(Period::Week),
+            "next-month" => Self::Next(Period::Month),
+            "next-year" => Self::Next(Period::Year),
+            s => {
+                if let Some(offset) = s.strip_prefix("today") {
+                    // `today3d` would be odd, so a sign is required.
+                    if !offset.starts_with(['+', '-']) {
+                        return Err(TodoParseError::BadDate);
+                    }
+                    let (amount, unit) = parse_offset(offset).ok_or(TodoParseError::BadDate)?;
+                    Self::Offset(amount, unit)
+                } else if let Some(weekday) = s.strip_prefix("next-") {
+                    Self::NextWeekday(weekday.parse().map_err(|_| TodoParseError::BadDate)?)
+                } else if let Ok(weekday) = s.parse() {
+                    Self::Weekday(weekday)
+                } else {
+                    let (amount, unit) = parse_offset(s).ok_or(TodoParseError::BadDate)?;
+                    Self::Offset(amount, unit)
+                }
+            }
+        };
+
+        Ok(date)
+    }
+}
+
+impl Display for RelativeDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let weekday = |weekday: Weekday| weekday.to_string().to_lowercase();
+
+        match *self {
+            Self::Fixed(date) => write!(f, "{}", date.format("%F")),
+            Self::Offset(0, RecurUnit::Day) => write!(f, "today"),
+            Self::Offset(1, RecurUnit::Day) => write!(f, "tomorrow"),
+            Self::Offset(-1, RecurUnit::Day) => write!(f, "yesterday"),
+            Self::Offset(amount, unit) => {
+                let rec = Recurrence {
+                    strict: false,
+                    amount: amount.unsigned_abs() as u32,
+                    unit,
+                };
+                write!(f, "{}{rec}", if amount < 0 { '-' } else { '+' })
+            }
+            Self::Weekday(day) => write!(f, "{}", weekday(day)),
+            Self::NextWeekday(day) => write!(f, "next-{}", weekday(day)),
+            Self::EndOf(period) => write!(f, "eo{}", &period.name()[..1]),
+            Self::Next(period) => write!(f, "next-{}", period.name()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Pluggable rendering of todos, columns and tables.
+//!
+//! Rendering walks a table column by column, a column todo by todo with
+//! subtasks under their parents, and a todo element by element, in todo.txt
+//! order. A `Renderer` decides how each step looks; the walk is the same for
+//! every output format.
+//!
+//! This module provides `Plain`, the todo.txt text used by `Display`, and
+//! `Ansi`, the terminal colors used by `Todo::colored`. `html::Html` and
+//! `markdown::Markdown` are renderers too.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::render::{Element, Renderer};
+//!
+//! /// Shouts projects.
+//! struct Loud;
+//!
+//! impl Renderer for Loud {
+//!     fn element(&self, _: &Todo, element: Element, text: &str) -> String {
+//!         match element {
+//!             Element::Project => text.to_uppercase(),
+//!             _ => text.to_string(),
+//!         }
+//!     }
+//! }
+//!
+//! let todo = Todo::from_str("(B) Call mom +family").unwrap();
+//! assert_eq!(todo.render(&Loud), "(B) Call mom +FAMILY");
+//! ```
+
+use crate::colors::{StyleScheme, RESET};
+use crate::{Todo, TodoColumn, TodoTable};
+
+/// What a piece of a rendered todo is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    /// The `x` of a completed todo.
+    Tick,
+    Priority,
+    Completion,
+    Creation,
+    /// A word of the description.
+    Description,
+    Project,
+    Context,
+    Deadline,
+    Threshold,
+    Metadata,
+}
+
+impl Element {
+    /// Returns the style of the element.
+    pub fn style<'a>(&self, style: StyleScheme<'a>) -> &'a str {
+        match self {
+            Self::Tick => style.tick,
+            Self::Priority => style.priority,
+            Self::Completion => style.completion,
+            Self::Creation => style.creation,
+            Self::Description => style.description,
+            Self::Project => style.project,
+            Self::Context => style.context,
+            Self::Deadline => style.deadline,
+            Self::Threshold | Self::Metadata => style.metadata,
+        }
+    }
+}
+
+/// Callbacks for each step of rendering.
+///
+/// Only `element` is required; the other steps default to the plain text
+/// layout.
+pub trait Renderer {
+    /// Renders an element of a todo, given as plain text.
+    fn element(&self, todo: &Todo, element: Element, text: &str) -> String;
+
+    /// Renders a todo from its rendered elements.
+    fn todo(&self, _todo: &Todo, elements: Vec<(Element, String)>) -> String {
+        elements
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Renders a column from its rendered todos, along with how deep each
+    /// is nested below its parents.
+    fn column(&self, col: &TodoColumn, todos: Vec<(usize, String)>) -> String {
+        let mut s = format!("| {} |\n", col.title);
+        for (depth, todo) in todos {
+            s.push_str(&format!("| {}{todo}\n", "  ".repeat(depth)));
+        }
+
+        s
+    }
+
+    /// Renders a table from its rendered columns.
+    fn table(&self, table: &TodoTable, columns: Vec<String>) -> String {
+        let mut s = format!("=== {} ===\n", table.title());
+        for col in columns {
+            s.push_str(&col);
+            s.push('\n');
+        }
+
+        s
+    }
+}
+
+/// Renders todo.txt text, without any styling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Plain;
+
+impl Renderer for Plain {
+    fn element(&self, _: &Todo, _: Element, text: &str) -> String {
+        text.to_string()
+    }
+}
+
+/// Renders todo.txt text with ANSI escape codes.
+///
+/// Completed todos are rendered in the faded style as a whole. Elements
+/// without a style are left as-is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ansi<'a> {
+    pub style: StyleScheme<'a>,
+}
+
+impl<'a> Ansi<'a> {
+    pub fn new(style: StyleScheme<'a>) -> Self {
+        Self { style }
+    }
+}
+
+impl Renderer for Ansi<'_> {
+    fn element(&self, todo: &Todo, element: Element, text: &str) -> String {
+        styled(element.style(self.style), text, !todo.completed)
+    }
+
+    fn todo(&self, todo: &Todo, elements: Vec<(Element, String)>) -> String {
+        let text = Plain.todo(todo, elements);
+        styled(self.style.faded, &text, todo.completed)
+    }
+}
+
+/// Wraps text in a style, unless it's empty or shouldn't apply.
+fn styled(style: &str, text: &str, apply: bool) -> String {
+    if apply && !style.is_empty() {
+        format!("{style}{text}{RESET}")
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,153 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! # Structured schema
+//!
+//! Every type is (de)serialized field by field, shown here as JSON:
+//!
+//! - `TodoTable`: `{ "title": string, "columns": [TodoColumn] }`
+//! - `TodoColumn`: `{ "todos": [Todo], "title": string }`
+//! - `TodoList`: `{ "lines": [TodoLine] }`; parse errors and line endings
+//!   are not kept
+//! - `TodoLine`: `{ "todo": Todo }`, `{ "blank": string }`,
+//!   `{ "comment": string }` or `{ "invalid": string }`
+//! - `Todo`: `{ "description", "completed", "priority", "metadata",
+//!   "deadline", "threshold", "creation", "completion_date" }`; every field
+//!   is optional when deserializing
+//! - `TodoDescription`: `[TodoSegment]`; in-place `key:value` tokens kept
+//!   by `Todo::parse_lossless` are left out, as their values are already
+//!   in `metadata`, `deadline` and `threshold`
+//! - `TodoSegment`: `{ "string": string }` or `{ "tag": TodoTag }`
+//! - `TodoTag`: `{ "project": string }` or `{ "context": string }`
+//! - `TodoPriority`: `"None"` or a letter from `"A"` to `"Z"`
+//! - `TodoDate`: `"never"`, `"always"`, `{ "day": datetime }`,
+//!   `{ "timed": datetime }` or `{ "zoned": zoned datetime }`
+//! - `Map`: an object, keeping the order of its keys
+//! - datetimes: ISO 8601 strings without an offset, e.g.
+//!   `"2024-01-31T00:00:00"`
+//! - zoned datetimes: RFC 3339 strings, e.g. `"2024-01-31T14:30:00+02:00"`
+//!
+//! # todo.txt schema
+//!
+//! To store todos as todo.txt lines instead, use the [`todo_txt`] and
+//! [`todo_txt_seq`] modules with `#[serde(with = "...")]`. Lines are parsed
+//! with `Todo::parse_lossless`, so in-place tokens keep their position.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "todo_lib::serialize::todo_txt")]
+//!     reminder: Todo,
+//! }
+//!
+//! let json = r#"{"reminder":"(A) Call mom +family due:2024-01-01"}"#;
+//! let config: Config = serde_json::from_str(json).unwrap();
+//!
+//! assert!(config.reminder.has_project_tag("family"));
+//! assert_eq!(serde_json::to_string(&config).unwrap(), json);
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::helper::Map;
+use crate::TodoDescription;
+
+impl Serialize for TodoDescription {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().filter(|seg| !seg.is_inline_meta()))
+    }
+}
+
+impl<K: PartialEq + Serialize, V: PartialEq + Serialize> Serialize for Map<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.data.len()))?;
+        for (k, v) in &self.data {
+            map.serialize_entry(k, v)?;
+        }
+
+        map.end()
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Map<K, V>
+where
+    K: PartialEq + Deserialize<'de>,
+    V: PartialEq + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<K, V>(PhantomData<(K, V)>);
+
+        impl<'de, K, V> Visitor<'de> for MapVisitor<K, V>
+        where
+            K: PartialEq + Deserialize<'de>,
+            V: PartialEq + Deserialize<'de>,
+        {
+            type Value = Map<K, V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut map = Map::new();
+                while let Some((k, v)) = access.next_entry()? {
+                    map.insert(k, v);
+                }
+
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+/// (De)serializes a `Todo` as a todo.txt line.
+pub mod todo_txt {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Todo;
+
+    pub fn serialize<S: Serializer>(todo: &Todo, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(todo)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Todo, D::Error> {
+        let line = String::deserialize(deserializer)?;
+        Todo::parse_lossless(line).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serializes a sequence of `Todo`s as todo.txt lines.
+pub mod todo_txt_seq {
+    use serde::de::Error;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Todo;
+
+    pub fn serialize<S: Serializer>(todos: &[Todo], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(todos.len()))?;
+        for todo in todos {
+            seq.serialize_element(&todo.to_string())?;
+        }
+
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Todo>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|line| Todo::parse_lossless(line).map_err(D::Error::custom))
+            .collect()
+    }
+}
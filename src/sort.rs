@@ -0,0 +1,155 @@
+//! Multi-key sorting of todos.
+//!
+//! Todos are compared by each key in turn, falling through to the next key
+//! on a tie. Todos missing the value of a key (`TodoPriority::None`,
+//! `TodoDate::Never`, no creation date, no project tag, ...) always sort
+//! after those that have it, regardless of the order.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::sort::{SortBy, SortKey};
+//!
+//! let mut col = TodoColumn::new("Todo");
+//! col.add(Todo::from_str("Someday").unwrap());
+//! col.add(Todo::from_str("(B) Later due:2030-01-01").unwrap());
+//! col.add(Todo::from_str("(B) Sooner due:2020-01-01").unwrap());
+//! col.add(Todo::from_str("(A) Now").unwrap());
+//!
+//! col.sort_by_keys(&[SortBy::asc(SortKey::Priority), SortBy::asc(SortKey::Deadline)]);
+//!
+//! let order: Vec<String> = col.iter().map(|t| t.to_string()).collect();
+//! assert_eq!(
+//!     order,
+//!     ["(A) Now", "(B) Sooner due:2020-01-01", "(B) Later due:2030-01-01", "Someday"],
+//! );
+//! ```
+
+use std::cmp::Ordering;
+
+use chrono::NaiveDateTime;
+
+use crate::{Todo, TodoDate, TodoSegment, TodoTag};
+
+/// A value to sort todos by.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// Priority, where ascending is A to Z.
+    Priority,
+    Deadline,
+    Threshold,
+    Creation,
+    Completion,
+    /// The alphabetically first project tag.
+    Project,
+    /// The alphabetically first context tag.
+    Context,
+    /// The value of a metadata key.
+    Meta(String),
+    /// The description text, ignoring case.
+    Description,
+}
+
+/// The direction to sort in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// A sort key along with its direction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SortBy {
+    pub key: SortKey,
+    pub order: SortOrder,
+}
+
+impl SortBy {
+    /// Sorts by the key in ascending order.
+    pub fn asc(key: SortKey) -> Self {
+        Self {
+            key,
+            order: SortOrder::Ascending,
+        }
+    }
+
+    /// Sorts by the key in descending order.
+    pub fn desc(key: SortKey) -> Self {
+        Self {
+            key,
+            order: SortOrder::Descending,
+        }
+    }
+
+    /// Compares two todos by this key.
+    pub fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        match &self.key {
+            SortKey::Priority => {
+                self.compare_values(a, b, |t| t.priority.is_some().then_some(t.priority as u8))
+            }
+            SortKey::Deadline => self.compare_values(a, b, |t| match t.deadline {
+                TodoDate::Always => Some(NaiveDateTime::MIN),
+                ref deadline => deadline.local(),
+            }),
+            SortKey::Threshold => self.compare_values(a, b, |t| t.threshold),
+            SortKey::Creation => self.compare_values(a, b, |t| t.creation),
+            SortKey::Completion => self.compare_values(a, b, |t| t.completion_date),
+            SortKey::Project => self.compare_values(a, b, |t| {
+                first_tag(t, |tag| match tag {
+                    TodoTag::Project(p) => Some(p),
+                    _ => None,
+                })
+            }),
+            SortKey::Context => self.compare_values(a, b, |t| {
+                first_tag(t, |tag| match tag {
+                    TodoTag::Context(c) => Some(c),
+                    _ => None,
+                })
+            }),
+            SortKey::Meta(key) => self.compare_values(a, b, |t| t.get_meta(key).cloned()),
+            SortKey::Description => {
+                self.compare_values(a, b, |t| Some(t.description.to_string().to_lowercase()))
+            }
+        }
+    }
+
+    /// Compares two optional values, with missing values last.
+    fn compare_values<T: Ord, F: Fn(&Todo) -> Option<T>>(
+        &self,
+        a: &Todo,
+        b: &Todo,
+        value: F,
+    ) -> Ordering {
+        match (value(a), value(b)) {
+            (Some(a), Some(b)) => match self.order {
+                SortOrder::Ascending => a.cmp(&b),
+                SortOrder::Descending => b.cmp(&a),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// Returns the alphabetically first tag selected by `select`.
+fn first_tag<F: Fn(&TodoTag) -> Option<&String>>(todo: &Todo, select: F) -> Option<String> {
+    todo.description
+        .0
+        .iter()
+        .filter_map(|seg| match seg {
+            TodoSegment::Tag(tag) => select(tag),
+            _ => None,
+        })
+        .min()
+        .cloned()
+}
+
+/// Compares two todos by each key in turn.
+pub fn compare(a: &Todo, b: &Todo, keys: &[SortBy]) -> Ordering {
+    keys.iter()
+        .map(|key| key.compare(a, b))
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
@@ -1,6 +1,23 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
 
-use crate::{colors::StyleScheme, helper::IsDue, Todo};
+use chrono::NaiveDateTime;
+
+use crate::{
+    archive::ArchiveOptions,
+    clock::{Clock, SystemClock},
+    colors::StyleScheme,
+    due::DuePolicy,
+    error::{LineError, TableParseError},
+    helper::IsDue,
+    query::Query,
+    render::{Ansi, Plain, Renderer},
+    sort::SortBy,
+    Todo, TodoDescription, TodoSegment,
+};
 
 /// A list of todos, under a title.
 ///
@@ -15,7 +32,8 @@ use crate::{colors::StyleScheme, helper::IsDue, Todo};
 /// todos.get("Buy mangos").expect("Failed to get todo").complete();
 /// todos.pop("Sort stamps").expect("Failed to remove todo");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TodoColumn {
     pub todos: Vec<Todo>,
     pub title: String,
@@ -35,10 +53,52 @@ impl TodoColumn {
         self.todos.push(todo);
     }
 
+    /// Adds a todo to the column, giving it a fresh ID if it doesn't have one.
+    /// Returns the todo's ID.
+    pub fn add_with_id(&mut self, mut todo: Todo) -> String {
+        let id = match todo.id() {
+            Some(id) => id.clone(),
+            None => next_id(self.todos.iter()),
+        };
+
+        todo.set_id(&id);
+        self.todos.push(todo);
+        id
+    }
+
+    /// Gives every todo without an `id:` tag a fresh one.
+    pub fn assign_ids(&mut self) {
+        assign_ids(self.todos.iter_mut());
+    }
+
+    /// Searches for the todo by ID. If found, returns a mutable reference to it.
+    pub fn get_by_id<S: AsRef<str>>(&mut self, id: S) -> Option<&mut Todo> {
+        self.todos
+            .iter_mut()
+            .find(|todo| todo.id().is_some_and(|i| i == id.as_ref()))
+    }
+
+    /// Searches for the todo by ID. If found, replaces it, keeping the ID,
+    /// and returns the old todo.
+    pub fn update_by_id<S: AsRef<str>>(&mut self, id: S, mut todo: Todo) -> Option<Todo> {
+        let old = self.get_by_id(id.as_ref())?;
+        todo.set_id(id.as_ref());
+        Some(std::mem::replace(old, todo))
+    }
+
+    /// Searches for the todo by ID. If found, returns it, and removes it from the column.
+    pub fn remove_by_id<S: AsRef<str>>(&mut self, id: S) -> Option<Todo> {
+        let i = self
+            .todos
+            .iter()
+            .position(|todo| todo.id().is_some_and(|i| i == id.as_ref()))?;
+        Some(self.todos.remove(i))
+    }
+
     /// Searches for the todo by title. If found, returns it, and removes it from the column.
     pub fn pop<S: ToString>(&mut self, title: S) -> Option<Todo> {
         for (i, todo) in self.todos.iter().enumerate() {
-            if todo.description.to_string(StyleScheme::default(), "") == title.to_string() {
+            if has_title(todo, &title.to_string()) {
                 return Some(self.todos.remove(i));
             }
         }
@@ -48,9 +108,9 @@ impl TodoColumn {
 
     /// Searches for the todo by title. If found, returns a mutable reference to it.
     pub fn get<S: ToString>(&mut self, title: S) -> Option<&mut Todo> {
-        self.todos.iter_mut().find(|todo| {
-            todo.description.to_string(StyleScheme::default(), "") == title.to_string()
-        })
+        self.todos
+            .iter_mut()
+            .find(|todo| has_title(todo, &title.to_string()))
     }
 
     /// Returns the first todo found with a given metadata key.
@@ -68,7 +128,7 @@ impl TodoColumn {
     pub fn get_meta<S: ToString>(&mut self, key: S, val: S) -> Option<&mut Todo> {
         self.todos.iter_mut().find(|todo| {
             todo.get_meta(key.to_string())
-                .map_or(false, |v| v == &val.to_string())
+                .is_some_and(|v| v == &val.to_string())
         })
     }
 
@@ -81,6 +141,248 @@ impl TodoColumn {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Todo> {
         self.todos.iter_mut()
     }
+
+    /// Returns an iterator over the todos which aren't hidden by a
+    /// threshold date.
+    pub fn iter_visible(&self) -> impl Iterator<Item = &Todo> {
+        self.iter_visible_at(SystemClock.now())
+    }
+
+    /// Returns an iterator over the todos which aren't hidden by a
+    /// threshold date at the given time.
+    pub fn iter_visible_at(&self, now: NaiveDateTime) -> impl Iterator<Item = &Todo> {
+        self.todos
+            .iter()
+            .filter(move |todo| !todo.is_hidden_at(now))
+    }
+
+    /// Returns an iterator over the todos which are actionable.
+    pub fn iter_actionable(&self) -> impl Iterator<Item = &Todo> {
+        self.iter_actionable_at(SystemClock.now())
+    }
+
+    /// Returns an iterator over the todos which are actionable at the given
+    /// time.
+    pub fn iter_actionable_at(&self, now: NaiveDateTime) -> impl Iterator<Item = &Todo> {
+        self.todos
+            .iter()
+            .filter(move |todo| todo.is_actionable_at(now))
+    }
+
+    /// Returns all the todos matching a query.
+    pub fn query(&self, query: &Query) -> Vec<&Todo> {
+        query.filter(self.todos.iter())
+    }
+
+    /// Moves completed todos to the end of another column.
+    ///
+    /// Returns how many todos were archived.
+    pub fn archive(&mut self, done: &mut TodoColumn, options: ArchiveOptions) -> usize {
+        self.archive_at(done, options, SystemClock.now())
+    }
+
+    /// Moves completed todos to the end of another column, as of the given
+    /// time.
+    pub fn archive_at(
+        &mut self,
+        done: &mut TodoColumn,
+        options: ArchiveOptions,
+        now: NaiveDateTime,
+    ) -> usize {
+        let archived = options.take(&mut self.todos, now);
+        let count = archived.len();
+        done.todos.extend(archived);
+        count
+    }
+
+    /// Returns the todos whose `parent:` tag is the given ID.
+    pub fn children<S: AsRef<str>>(&self, id: S) -> Vec<&Todo> {
+        self.todos
+            .iter()
+            .filter(|todo| todo.parent().is_some_and(|p| p == id.as_ref()))
+            .collect()
+    }
+
+    /// Returns the todo with the given ID and all its descendants,
+    /// depth-first, along with their depth below it.
+    pub fn subtree<S: AsRef<str>>(&self, id: S) -> Vec<(usize, &Todo)> {
+        let mut out = Vec::new();
+        if let Some(i) = self.index_of(id.as_ref()) {
+            let children = self.child_indices();
+            self.walk(i, &children, &mut vec![false; self.todos.len()], &mut out);
+        }
+
+        out
+    }
+
+    /// Returns the percentage (0 to 100) of the todo's direct children
+    /// which are complete, or None if it has no children. Grandchildren
+    /// only count through their parent's `completed`.
+    pub fn completion_percentage<S: AsRef<str>>(&self, id: S) -> Option<f32> {
+        let children = self.children(id);
+        if children.is_empty() {
+            return None;
+        }
+        let done = children.iter().filter(|t| t.completed).count();
+
+        Some(done as f32 * 100.0 / children.len() as f32)
+    }
+
+    /// Completes the todo with the given ID, adding its next instance if it
+    /// recurs. If `complete_parents` is set, parents whose children are all
+    /// complete are completed as well.
+    ///
+    /// Returns false if there is no todo with the ID.
+    pub fn complete_by_id<S: AsRef<str>>(&mut self, id: S, complete_parents: bool) -> bool {
+        self.complete_by_id_at(id, complete_parents, SystemClock.now())
+    }
+
+    /// Completes the todo with the given ID at the given time, like
+    /// `complete_by_id`.
+    pub fn complete_by_id_at<S: AsRef<str>>(
+        &mut self,
+        id: S,
+        complete_parents: bool,
+        now: NaiveDateTime,
+    ) -> bool {
+        let Some(next) = self.complete_subtree(id.as_ref(), complete_parents, now) else {
+            return false;
+        };
+
+        for todo in next {
+            self.add_with_id(todo);
+        }
+
+        true
+    }
+
+    /// Completes the todo with the given ID, and its parents if
+    /// `complete_parents` is set, returning the next instances of those
+    /// which recur, without IDs. Returns None if there is no todo with the
+    /// ID.
+    fn complete_subtree(
+        &mut self,
+        id: &str,
+        complete_parents: bool,
+        now: NaiveDateTime,
+    ) -> Option<Vec<Todo>> {
+        let todo = self.get_by_id(id)?;
+
+        let mut parent = todo.parent().cloned();
+        let mut next: Vec<Todo> = todo.complete_at(now).into_iter().collect();
+
+        while let Some(id) = parent.take().filter(|_| complete_parents) {
+            if self.children(&id).iter().any(|child| !child.completed) {
+                break;
+            }
+
+            match self.get_by_id(&id) {
+                Some(todo) if !todo.completed => {
+                    parent = todo.parent().cloned();
+                    next.extend(todo.complete_at(now));
+                }
+                _ => break,
+            }
+        }
+
+        Some(next)
+    }
+
+    /// Colorizes the column as a string.
+    pub fn colored(&self, style: StyleScheme) -> String {
+        self.render(&Ansi::new(style))
+    }
+
+    /// Renders the column with a renderer, subtasks below their parents.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
+        let todos = self
+            .tree()
+            .into_iter()
+            .map(|(depth, todo)| (depth, todo.render(renderer)))
+            .collect();
+
+        renderer.column(self, todos)
+    }
+
+    /// Returns every todo with its depth, children following their parents.
+    ///
+    /// Todos whose parent isn't in the column are treated as top-level.
+    pub(crate) fn tree(&self) -> Vec<(usize, &Todo)> {
+        let children = self.child_indices();
+        let mut is_root = vec![true; self.todos.len()];
+        for &j in children.iter().flatten() {
+            is_root[j] = false;
+        }
+
+        let mut visited = vec![false; self.todos.len()];
+        let mut out = Vec::new();
+
+        for (i, _) in is_root.iter().enumerate().filter(|(_, root)| **root) {
+            self.walk(i, &children, &mut visited, &mut out);
+        }
+
+        // Todos in a parent cycle are never reached from a root.
+        for i in 0..self.todos.len() {
+            if !visited[i] {
+                self.walk(i, &children, &mut visited, &mut out);
+            }
+        }
+
+        out
+    }
+
+    /// Returns the indices of each todo's children, in order.
+    ///
+    /// If several todos share an ID, the first one is the parent.
+    fn child_indices(&self) -> Vec<Vec<usize>> {
+        let mut ids = HashMap::new();
+        for (i, todo) in self.todos.iter().enumerate() {
+            if let Some(id) = todo.id() {
+                ids.entry(id.as_str()).or_insert(i);
+            }
+        }
+
+        let mut children = vec![Vec::new(); self.todos.len()];
+        for (j, todo) in self.todos.iter().enumerate() {
+            if let Some(&i) = todo.parent().and_then(|p| ids.get(p.as_str())) {
+                children[i].push(j);
+            }
+        }
+
+        children
+    }
+
+    /// Walks the todo and its unvisited descendants depth-first, without
+    /// recursing, so that deep chains can't overflow the stack.
+    fn walk<'a>(
+        &'a self,
+        root: usize,
+        children: &[Vec<usize>],
+        visited: &mut [bool],
+        out: &mut Vec<(usize, &'a Todo)>,
+    ) {
+        let mut stack = vec![(root, 0)];
+        while let Some((i, depth)) = stack.pop() {
+            if visited[i] {
+                continue;
+            }
+
+            visited[i] = true;
+            out.push((depth, &self.todos[i]));
+            stack.extend(children[i].iter().rev().map(|&j| (j, depth + 1)));
+        }
+    }
+
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.todos
+            .iter()
+            .position(|todo| todo.id().is_some_and(|i| i == id))
+    }
+
+    /// Sorts the todos by each key in turn. The sort is stable.
+    pub fn sort_by_keys(&mut self, keys: &[SortBy]) {
+        self.todos.sort_by(|a, b| crate::sort::compare(a, b, keys));
+    }
 }
 
 impl IntoIterator for TodoColumn {
@@ -94,19 +396,18 @@ impl IntoIterator for TodoColumn {
 
 impl IsDue for TodoColumn {
     /// Returns true if any the of the contained todos are due.
-    fn due(&self) -> bool {
-        self.todos.iter().any(|t| t.due())
+    fn due_at(&self, now: NaiveDateTime) -> bool {
+        self.due_with(now, DuePolicy::default())
+    }
+
+    fn due_with(&self, now: NaiveDateTime, policy: DuePolicy) -> bool {
+        self.todos.iter().any(|t| t.due_with(now, policy))
     }
 }
 
 impl Display for TodoColumn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "| {} |", self.title)?;
-        for todo in self.todos.iter() {
-            writeln!(f, "| {todo}")?;
-        }
-
-        Ok(())
+        write!(f, "{}", self.render(&Plain))
     }
 }
 
@@ -135,27 +436,29 @@ impl Display for TodoColumn {
 ///
 /// todo2.unwrap().complete();
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TodoTable {
     title: String,
     columns: Vec<TodoColumn>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) errors: Vec<LineError>,
 }
 
 impl IsDue for TodoTable {
     /// Returns true if any the of the contained columns contain a todo which is due.
-    fn due(&self) -> bool {
-        self.columns.iter().any(|tt| tt.due())
+    fn due_at(&self, now: NaiveDateTime) -> bool {
+        self.due_with(now, DuePolicy::default())
+    }
+
+    fn due_with(&self, now: NaiveDateTime, policy: DuePolicy) -> bool {
+        self.columns.iter().any(|tt| tt.due_with(now, policy))
     }
 }
 
 impl Display for TodoTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "=== {} ===", self.title)?;
-        for col in self.columns.iter() {
-            writeln!(f, "{col}\n")?;
-        }
-
-        Ok(())
+        write!(f, "{}", self.render(&Plain))
     }
 }
 
@@ -165,6 +468,7 @@ impl TodoTable {
         TodoTable {
             title: title.map_or("Todos".into(), |s| s.to_string()),
             columns: Vec::new(),
+            errors: Vec::new(),
         }
     }
 
@@ -173,6 +477,36 @@ impl TodoTable {
         self.columns.push(TodoColumn::new(title));
     }
 
+    /// Colorizes the table as a string.
+    pub fn colored(&self, style: StyleScheme) -> String {
+        self.render(&Ansi::new(style))
+    }
+
+    /// Renders the table with a renderer.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|col| col.render(renderer))
+            .collect();
+        renderer.table(self, columns)
+    }
+
+    /// Returns the title of the table.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the columns of the table, in order.
+    pub fn columns(&self) -> &[TodoColumn] {
+        &self.columns
+    }
+
+    /// Returns the columns of the table, in order.
+    pub fn columns_mut(&mut self) -> &mut [TodoColumn] {
+        &mut self.columns
+    }
+
     /// Searches for the todo by title in a column.
     /// If found, returns a mutable reference to it.
     pub fn get_todo<S: ToString>(&mut self, title: S, col_title: S) -> Option<&mut Todo> {
@@ -193,6 +527,101 @@ impl TodoTable {
         }
     }
 
+    /// Adds a todo to a column, giving it an ID unique across the table if
+    /// it doesn't have one. Returns the todo's ID, or None if the column
+    /// doesn't exist.
+    pub fn add_todo_with_id<S: ToString>(
+        &mut self,
+        mut todo: Todo,
+        col_title: S,
+    ) -> Option<String> {
+        let id = match todo.id() {
+            Some(id) => id.clone(),
+            None => next_id(self.iter()),
+        };
+
+        todo.set_id(&id);
+        self.col(col_title)?.add(todo);
+        Some(id)
+    }
+
+    /// Gives every todo without an `id:` tag one unique across the table.
+    pub fn assign_ids(&mut self) {
+        assign_ids(self.iter_mut());
+    }
+
+    /// Completes the todo with the given ID like `TodoColumn::complete_by_id`,
+    /// giving the next instances of recurring todos IDs unique across the
+    /// table.
+    ///
+    /// Returns false if there is no todo with the ID.
+    pub fn complete_todo_by_id<S: AsRef<str>>(&mut self, id: S, complete_parents: bool) -> bool {
+        self.complete_todo_by_id_at(id, complete_parents, SystemClock.now())
+    }
+
+    /// Completes the todo with the given ID at the given time, like
+    /// `complete_todo_by_id`.
+    pub fn complete_todo_by_id_at<S: AsRef<str>>(
+        &mut self,
+        id: S,
+        complete_parents: bool,
+        now: NaiveDateTime,
+    ) -> bool {
+        let Some((i, next)) = self.columns.iter_mut().enumerate().find_map(|(i, col)| {
+            Some((i, col.complete_subtree(id.as_ref(), complete_parents, now)?))
+        }) else {
+            return false;
+        };
+
+        for mut todo in next {
+            todo.set_id(next_id(self.iter()));
+            self.columns[i].add(todo);
+        }
+
+        true
+    }
+
+    /// Searches every column for the todo by ID.
+    /// If found, returns a mutable reference to it.
+    pub fn get_todo_by_id<S: AsRef<str>>(&mut self, id: S) -> Option<&mut Todo> {
+        self.columns
+            .iter_mut()
+            .find_map(|col| col.get_by_id(id.as_ref()))
+    }
+
+    /// Searches every column for the todo by ID. If found, replaces it,
+    /// keeping the ID, and returns the old todo.
+    pub fn update_todo_by_id<S: AsRef<str>>(&mut self, id: S, todo: Todo) -> Option<Todo> {
+        self.columns
+            .iter_mut()
+            .find(|col| col.iter().any(|t| t.id().is_some_and(|i| i == id.as_ref())))?
+            .update_by_id(id, todo)
+    }
+
+    /// Searches every column for the todo by ID.
+    /// If found, returns it, and removes it from its column.
+    pub fn remove_todo_by_id<S: AsRef<str>>(&mut self, id: S) -> Option<Todo> {
+        self.columns
+            .iter_mut()
+            .find_map(|col| col.remove_by_id(id.as_ref()))
+    }
+
+    /// Moves a todo, found by ID, to another column.
+    /// If the column or the todo doesn't exist, returns false.
+    pub fn move_todo_by_id<S: AsRef<str>, T: ToString>(&mut self, id: S, to: T) -> bool {
+        let to = to.to_string();
+        if !self.columns.iter().any(|col| col.title == to) {
+            return false;
+        }
+
+        let Some(todo) = self.remove_todo_by_id(id) else {
+            return false;
+        };
+
+        self.add_todo(todo, to);
+        true
+    }
+
     /// Moves a todo from one column to another.
     /// If either column or the todo doesn't exist, returns false.
     pub fn move_todo<S: ToString>(&mut self, title: S, from: S, to: S) -> bool {
@@ -216,6 +645,55 @@ impl TodoTable {
         true
     }
 
+    /// Returns an iterator over the todos in every column.
+    pub fn iter(&self) -> impl Iterator<Item = &Todo> {
+        self.columns.iter().flat_map(|col| col.iter())
+    }
+
+    /// Returns a mutable iterator over the todos in every column.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Todo> {
+        self.columns.iter_mut().flat_map(|col| col.iter_mut())
+    }
+
+    /// Returns an iterator over the todos in every column which aren't
+    /// hidden by a threshold date.
+    pub fn iter_visible(&self) -> impl Iterator<Item = &Todo> {
+        self.iter_visible_at(SystemClock.now())
+    }
+
+    /// Returns an iterator over the todos in every column which aren't
+    /// hidden by a threshold date at the given time.
+    pub fn iter_visible_at(&self, now: NaiveDateTime) -> impl Iterator<Item = &Todo> {
+        self.columns
+            .iter()
+            .flat_map(move |col| col.iter_visible_at(now))
+    }
+
+    /// Returns an iterator over the actionable todos in every column.
+    pub fn iter_actionable(&self) -> impl Iterator<Item = &Todo> {
+        self.iter_actionable_at(SystemClock.now())
+    }
+
+    /// Returns an iterator over the todos in every column which are
+    /// actionable at the given time.
+    pub fn iter_actionable_at(&self, now: NaiveDateTime) -> impl Iterator<Item = &Todo> {
+        self.columns
+            .iter()
+            .flat_map(move |col| col.iter_actionable_at(now))
+    }
+
+    /// Returns all the todos in every column matching a query.
+    pub fn query(&self, query: &Query) -> Vec<&Todo> {
+        query.filter(self.iter())
+    }
+
+    /// Sorts the todos in every column by each key in turn.
+    pub fn sort_by_keys(&mut self, keys: &[SortBy]) {
+        for col in self.columns.iter_mut() {
+            col.sort_by_keys(keys);
+        }
+    }
+
     /// Searches for a column by name. If found, returns a mutable reference.
     pub fn col<S: ToString>(&mut self, title: S) -> Option<&mut TodoColumn> {
         self.columns
@@ -243,3 +721,177 @@ impl TodoTable {
             .get_meta(key.to_string(), val.to_string())
     }
 }
+
+/// Persisting tables in a todo.txt-compatible file.
+///
+/// The table and its columns are stored as comment lines, which other
+/// todo.txt tools ignore:
+///
+/// ```text
+/// # table: Todos
+///
+/// # column: Work
+/// (A) Review documents
+///
+/// # column: Home
+/// Clean desk
+/// ```
+///
+/// Blank lines and other comments are ignored when reading. Todos which
+/// would read as one of those, because they're blank or start with `#`, are
+/// written after a `# todo: ` marker. Lines which fail to parse are kept as
+/// todos of plain text, so that they're written back unchanged, and reported
+/// in `TodoTable::errors`.
+///
+/// Example:
+/// ```
+/// use todo_lib::prelude::*;
+///
+/// let mut todos = TodoTable::new(Some("Todos"));
+/// todos.add_col("Work");
+/// todos.add_col("Home");
+/// todos.add_todo(Todo::from_str("(A) Review documents").unwrap(), "Work");
+///
+/// let text = todos.to_todo_txt();
+/// let loaded = TodoTable::parse_todo_txt(&text).unwrap();
+///
+/// assert_eq!(loaded.title(), "Todos");
+/// assert_eq!(loaded.columns().len(), 2);
+/// assert_eq!(loaded.to_todo_txt(), text);
+/// ```
+impl TodoTable {
+    const TABLE_HEADER: &'static str = "# table: ";
+    const COLUMN_HEADER: &'static str = "# column: ";
+    pub(crate) const TODO_HEADER: &'static str = "# todo: ";
+
+    /// Returns the table in the persistent todo.txt format.
+    pub fn to_todo_txt(&self) -> String {
+        let mut s = format!("{}{}\n", Self::TABLE_HEADER, self.title);
+        for col in &self.columns {
+            s.push_str(&format!("\n{}{}\n", Self::COLUMN_HEADER, col.title));
+            for todo in &col.todos {
+                let todo = todo.to_string();
+                if todo.trim().is_empty() || todo.starts_with('#') {
+                    s.push_str(Self::TODO_HEADER);
+                }
+                s.push_str(&format!("{todo}\n"));
+            }
+        }
+
+        s
+    }
+
+    /// Parses a table from the persistent todo.txt format.
+    ///
+    /// Only fails if a todo comes before any column; todos which fail to
+    /// parse are reported in `errors`.
+    pub fn parse_todo_txt<S: AsRef<str>>(text: S) -> Result<Self, TableParseError> {
+        let mut table = Self::new::<String>(None);
+        for (i, line) in text.as_ref().lines().enumerate() {
+            let line = if let Some(todo) = line.strip_prefix(Self::TODO_HEADER) {
+                todo
+            } else if line.trim().is_empty() {
+                continue;
+            } else if line.starts_with('#') {
+                if let Some(title) = line.strip_prefix(Self::TABLE_HEADER) {
+                    table.title = title.to_string();
+                } else if let Some(title) = line.strip_prefix(Self::COLUMN_HEADER) {
+                    table.add_col(title);
+                }
+                continue;
+            } else {
+                line
+            };
+
+            let todo = match Todo::parse_lossless(line) {
+                Ok(todo) => todo,
+                Err(error) => {
+                    table.errors.push(LineError { line: i + 1, error });
+                    Todo {
+                        description: TodoDescription(vec![TodoSegment::String(line.to_string())]),
+                        ..Todo::default()
+                    }
+                }
+            };
+            table
+                .columns
+                .last_mut()
+                .ok_or(TableParseError::MissingColumn(i + 1))?
+                .add(todo);
+        }
+
+        Ok(table)
+    }
+
+    /// Returns the errors encountered while parsing.
+    pub fn errors(&self) -> &[LineError] {
+        &self.errors
+    }
+
+    /// Reads a table in the persistent todo.txt format.
+    ///
+    /// Parse errors are returned as `io::ErrorKind::InvalidData`.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Self::parse_todo_txt(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a table in the persistent todo.txt format from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(File::open(path)?)
+    }
+
+    /// Writes the table in the persistent todo.txt format.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.to_todo_txt().as_bytes())?;
+        writer.flush()
+    }
+
+    /// Writes the table in the persistent todo.txt format to a file,
+    /// creating or truncating it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Returns true if the todo's description is the title, ignoring how the
+/// words are spaced, since lossless parsing keeps extra spaces.
+fn has_title(todo: &Todo, title: &str) -> bool {
+    let description = todo.description.to_string();
+    description.split_whitespace().eq(title.split_whitespace())
+}
+
+/// Returns an ID one greater than the highest numeric ID among the todos,
+/// or the lowest free one if that's the largest possible ID.
+pub(crate) fn next_id<'a, I: Iterator<Item = &'a Todo>>(todos: I) -> String {
+    fresh_ids(used_ids(todos))
+        .next()
+        .expect("there are fewer todos than IDs")
+        .to_string()
+}
+
+/// Gives every todo without an `id:` tag a fresh numeric one.
+fn assign_ids<'a, I: Iterator<Item = &'a mut Todo>>(todos: I) {
+    let todos: Vec<_> = todos.collect();
+    let mut ids = fresh_ids(used_ids(todos.iter().map(|todo| &**todo)));
+
+    for todo in todos.into_iter().filter(|todo| todo.id().is_none()) {
+        todo.set_id(ids.next().expect("there are fewer todos than IDs"));
+    }
+}
+
+/// Returns the numeric IDs of the todos.
+fn used_ids<'a, I: Iterator<Item = &'a Todo>>(todos: I) -> HashSet<u64> {
+    todos.filter_map(|todo| todo.id()?.parse().ok()).collect()
+}
+
+/// Returns the IDs above the highest one in use, followed by the free ones
+/// below it.
+fn fresh_ids(used: HashSet<u64>) -> impl Iterator<Item = u64> {
+    let max = used.iter().copied().max().unwrap_or(0);
+
+    let above = max.checked_add(1).map(|next| next..=u64::MAX);
+    let below = (1..max).filter(move |id| !used.contains(id));
+    above.into_iter().flatten().chain(below)
+}
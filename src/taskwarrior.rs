@@ -0,0 +1,331 @@
+//! Taskwarrior JSON import and export, enabled by the `taskwarrior` feature.
+//!
+//! Converts between todos and the JSON of `task export` and `task import`.
+//! Task fields map onto todos as follows:
+//!
+//! - `description`: the description text, without tags; any `+project` or
+//!   `@context` words in it are imported as text
+//! - `project`: the first project tag; any other project tags stay in the
+//!   description
+//! - `tags`: context tags
+//! - `priority`: `H`, `M` and `L` as `A`, `B` and `C`; `D` to `Z` are
+//!   exported as `L`
+//! - `entry`, `due`, `end` and `wait`: `creation`, `deadline`,
+//!   `completion_date` and `threshold`; a `due` time other than local
+//!   midnight is imported as a UTC deadline
+//! - `status`: `completed`; other statuses than `pending`, `waiting` and
+//!   `completed` are kept in the `status` metadata key
+//! - `uuid`: the `uuid` metadata key
+//! - `todotxt_id`: the `id` metadata key, since Taskwarrior's own `id` is a
+//!   working set number
+//!
+//! `id`, `urgency` and `modified` are computed by Taskwarrior, and are
+//! dropped. `dep:` and `blocks:` are exported like other metadata, as
+//! string UDAs listing `todotxt_id`s; they aren't turned into Taskwarrior's
+//! `depends`, which is imported as JSON metadata and has no effect on
+//! `deps`. Any other field, such as a UDA, is kept in `metadata`: strings
+//! as-is, other values as JSON after a `=`, like `estimate:=3`, so that
+//! they're exported with the same type. Since todo.txt metadata can't
+//! contain whitespace or colons, those are percent-encoded, along with `%`
+//! and a leading `=` in strings.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::taskwarrior;
+//!
+//! let json = r#"[{"uuid":"0b8f","description":"Buy milk","project":"home",
+//!     "tags":["errand"],"priority":"H","status":"pending","estimate":"2 hours"}]"#;
+//! let todos = taskwarrior::parse_export(json).unwrap();
+//!
+//! assert_eq!(todos[0].to_string(), "(A) Buy milk +home @errand uuid:0b8f estimate:2%20hours");
+//!
+//! let task = taskwarrior::to_task(&todos[0]);
+//! assert_eq!(task["estimate"], "2 hours");
+//! assert_eq!(task["tags"][0], "errand");
+//! ```
+
+use std::io::{self, Read, Write};
+
+use chrono::{NaiveTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::error::TaskwarriorError;
+use crate::helper::{percent_decode, percent_encode};
+use crate::ical::{format_utc, parse_date, parse_deadline};
+use crate::{Todo, TodoDate, TodoDescription, TodoPriority, TodoSegment, TodoTable, TodoTag};
+
+/// Fields which are computed by Taskwarrior, and not imported.
+const COMPUTED: [&str; 3] = ["id", "urgency", "modified"];
+
+/// Fields which map onto todo fields rather than metadata.
+const MAPPED: [&str; 11] = [
+    "description",
+    "project",
+    "tags",
+    "priority",
+    "entry",
+    "due",
+    "end",
+    "wait",
+    "status",
+    "uuid",
+    "todotxt_id",
+];
+
+/// Converts a task into a todo.
+pub fn from_task(task: &Value) -> Result<Todo, TaskwarriorError> {
+    let task = task.as_object().ok_or(TaskwarriorError::NotATask)?;
+    let string = |key: &str| task.get(key).and_then(Value::as_str);
+    let date = |key: &str| {
+        string(key)
+            .map(|date| parse_date(date).map_err(|_| TaskwarriorError::BadDate(date.to_string())))
+            .transpose()
+    };
+
+    // Unlike `Todo::new`, keeps the description as text, so that words such
+    // as `+milk` don't turn into tags.
+    let mut todo = Todo::default();
+    if let Some(description) = string("description").filter(|d| !d.is_empty()) {
+        let words = description
+            .split(' ')
+            .map(|w| TodoSegment::String(w.to_string()));
+        todo.description = TodoDescription(words.collect());
+    }
+
+    todo.creation = date("entry")?;
+    todo.completion_date = date("end")?;
+    todo.threshold = date("wait")?;
+    if let Some(due) = string("due") {
+        let deadline =
+            parse_deadline(due).map_err(|_| TaskwarriorError::BadDate(due.to_string()))?;
+        // Taskwarrior has no deadlines without a time, but stores dates as
+        // local midnight.
+        todo.deadline = match deadline.local() {
+            Some(t) if t.time() == NaiveTime::MIN => TodoDate::Day(t),
+            _ => deadline,
+        };
+    }
+
+    if let Some(project) = string("project").and_then(|p| TodoTag::project(percent_encode(p))) {
+        todo.description.0.push(TodoSegment::Tag(project));
+    }
+    for tag in task
+        .get("tags")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let Some(tag) = tag
+            .as_str()
+            .and_then(|t| TodoTag::context(percent_encode(t)))
+        {
+            todo.description.0.push(TodoSegment::Tag(tag));
+        }
+    }
+
+    todo.priority = match string("priority") {
+        None | Some("") => TodoPriority::None,
+        Some("H") => TodoPriority::A,
+        Some("M") => TodoPriority::B,
+        Some("L") => TodoPriority::C,
+        Some(p) => return Err(TaskwarriorError::BadPriority(p.to_string())),
+    };
+
+    if let Some(uuid) = string("uuid") {
+        todo.set_meta("uuid".to_string(), percent_encode(uuid));
+    }
+    let id = match task.get("todotxt_id") {
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(Value::Number(id)) => Some(id.to_string()),
+        _ => None,
+    };
+    if let Some(id) = id.filter(|id| !id.is_empty()) {
+        todo.set_meta("id".to_string(), percent_encode(&id));
+    }
+
+    match string("status") {
+        None | Some("pending" | "waiting") => {}
+        Some("completed") => {
+            // Keep the priority like `Todo::complete` does.
+            todo.completed = true;
+            if todo.priority.is_some() {
+                let pri = (todo.priority as u8 as char).to_string();
+                todo.set_meta("pri".to_string(), pri);
+                todo.priority = TodoPriority::None;
+            }
+        }
+        Some(status) => todo.set_meta("status".to_string(), percent_encode(status)),
+    }
+
+    for (key, val) in task {
+        if COMPUTED.contains(&key.as_str()) || MAPPED.contains(&key.as_str()) {
+            continue;
+        }
+
+        let val = match val {
+            Value::String(s) => match s.strip_prefix('=') {
+                Some(s) => format!("%3D{}", percent_encode(s)),
+                None => percent_encode(s),
+            },
+            val => format!("={}", percent_encode(&val.to_string())),
+        };
+        todo.set_meta(percent_encode(key), val);
+    }
+
+    Ok(todo)
+}
+
+/// Converts a todo into a task.
+pub fn to_task(todo: &Todo) -> Value {
+    let mut task = Map::new();
+
+    let mut description = Vec::new();
+    let mut project = None;
+    let mut tags = Vec::new();
+    for seg in &todo.description.0 {
+        match seg {
+            TodoSegment::Tag(TodoTag::Project(p)) if project.is_none() => project = Some(p),
+            TodoSegment::Tag(TodoTag::Context(c)) => tags.push(Value::from(percent_decode(c))),
+            seg if !seg.is_inline_meta() => description.push(seg.clone()),
+            _ => {}
+        }
+    }
+
+    let description = TodoDescription(description).to_string();
+    task.insert("description".into(), description.trim().into());
+    if let Some(project) = project {
+        task.insert("project".into(), percent_decode(project).into());
+    }
+    if !tags.is_empty() {
+        task.insert("tags".into(), tags.into());
+    }
+
+    let priority = match todo.priority {
+        TodoPriority::None => todo
+            .get_meta("pri")
+            .filter(|_| todo.completed)
+            .and_then(|p| TodoPriority::try_from(format!("({p})").as_str()).ok())
+            .unwrap_or_default(),
+        priority => priority,
+    };
+    match priority {
+        TodoPriority::None => {}
+        TodoPriority::A => {
+            task.insert("priority".into(), "H".into());
+        }
+        TodoPriority::B => {
+            task.insert("priority".into(), "M".into());
+        }
+        _ => {
+            task.insert("priority".into(), "L".into());
+        }
+    }
+
+    if let Some(date) = todo.creation {
+        task.insert("entry".into(), format_utc(date).into());
+    }
+    let due = match &todo.deadline {
+        TodoDate::Zoned(t) => Some(t.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()),
+        deadline => deadline.local().map(format_utc),
+    };
+    if let Some(due) = due {
+        task.insert("due".into(), due.into());
+    }
+    if let Some(date) = todo.completion_date.filter(|_| todo.completed) {
+        task.insert("end".into(), format_utc(date).into());
+    }
+    if let Some(date) = todo.threshold {
+        task.insert("wait".into(), format_utc(date).into());
+    }
+
+    let status = match todo.get_meta("status") {
+        Some(status) => percent_decode(status),
+        None if todo.completed => "completed".to_string(),
+        None => "pending".to_string(),
+    };
+    task.insert("status".into(), status.into());
+
+    for (key, val) in &todo.metadata.data {
+        let key = percent_decode(key);
+        match key.as_str() {
+            "uuid" => {
+                task.insert(key, percent_decode(val).into());
+            }
+            "id" => {
+                task.insert("todotxt_id".into(), percent_decode(val).into());
+            }
+            "pri" if todo.completed => {}
+            key if MAPPED.contains(&key) || COMPUTED.contains(&key) => {}
+            _ => {
+                let json = val
+                    .strip_prefix('=')
+                    .and_then(|json| serde_json::from_str(&percent_decode(json)).ok());
+                task.insert(key, json.unwrap_or_else(|| percent_decode(val).into()));
+            }
+        }
+    }
+
+    Value::Object(task)
+}
+
+/// Parses the JSON array of `task export`.
+pub fn parse_export<S: AsRef<str>>(json: S) -> Result<Vec<Todo>, TaskwarriorError> {
+    let tasks: Vec<Value> =
+        serde_json::from_str(json.as_ref()).map_err(|e| TaskwarriorError::Json(e.to_string()))?;
+
+    tasks.iter().map(from_task).collect()
+}
+
+/// Returns the todos as a JSON array for `task import`, one task per line.
+pub fn to_export<'a, I: IntoIterator<Item = &'a Todo>>(todos: I) -> String {
+    let tasks: Vec<String> = todos
+        .into_iter()
+        .map(|todo| to_task(todo).to_string())
+        .collect();
+
+    format!("[\n{}\n]\n", tasks.join(",\n"))
+}
+
+/// Parses the JSON array of `task export` into a table, with a column for
+/// each task status in order of appearance.
+pub fn parse_table<S: AsRef<str>, T: ToString>(
+    json: S,
+    title: Option<T>,
+) -> Result<TodoTable, TaskwarriorError> {
+    let tasks: Vec<Value> =
+        serde_json::from_str(json.as_ref()).map_err(|e| TaskwarriorError::Json(e.to_string()))?;
+
+    let mut table = TodoTable::new(title);
+    for task in &tasks {
+        let status = task
+            .get("status")
+            .and_then(Value::as_str)
+            .unwrap_or("pending");
+        if table.col(status).is_none() {
+            table.add_col(status);
+        }
+
+        table.add_todo(from_task(task)?, status);
+    }
+
+    Ok(table)
+}
+
+/// Reads the output of `task export`.
+///
+/// Parse errors are returned as `io::ErrorKind::InvalidData`.
+pub fn read<R: Read>(mut reader: R) -> io::Result<Vec<Todo>> {
+    let mut json = String::new();
+    reader.read_to_string(&mut json)?;
+    parse_export(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes the todos as input for `task import`.
+pub fn write<'a, W: Write, I: IntoIterator<Item = &'a Todo>>(
+    mut writer: W,
+    todos: I,
+) -> io::Result<()> {
+    writer.write_all(to_export(todos).as_bytes())?;
+    writer.flush()
+}
@@ -71,7 +71,7 @@ fn todo_display() {
     assert_eq!(
         todo.to_string(),
         format!(
-            "x (B) {0}-{1:02}-{2:02} {0}-{1:02}-{2:02} Todo #1 due:0000-00-00",
+            "x {0}-{1:02}-{2:02} {0}-{1:02}-{2:02} Todo #1 due:0000-00-00 pri:B",
             today.year(),
             today.month(),
             today.day()
@@ -183,3 +183,1776 @@ fn todo_txt_metadata() {
         "2023-01-16 Add metadata to the +todo @library due:2000-01-01 key:val",
     );
 }
+
+#[test]
+fn todo_list_roundtrip() {
+    let text =
+        "(A) 2023-01-07 Call mom +family\n\n# errands\nBuy milk @store due:xd\n   \nx Pay rent\n";
+    let list = TodoList::parse(text);
+
+    assert_eq!(list.lines().len(), 6);
+    assert_eq!(list.iter().count(), 2);
+
+    assert_eq!(list.errors().len(), 1);
+    assert_eq!(list.errors()[0].line, 4);
+
+    assert_eq!(list.to_string(), text);
+
+    let mut out = Vec::new();
+    list.write(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), text);
+
+    let read = TodoList::read(text.as_bytes()).unwrap();
+    assert!(read.iter().any(|todo| todo.completed));
+
+    // Todos which would read as comments or blank lines survive a reload.
+    let mut list = TodoList::new();
+    list.add(Todo::from_str("#hashtag stuff").unwrap());
+    list.add(Todo::default());
+    let text = list.to_string();
+    assert_eq!(text, "# todo: #hashtag stuff\n# todo: \n");
+    let reloaded = TodoList::parse(&text);
+    assert_eq!(reloaded.iter().count(), 2);
+    assert_eq!(reloaded.to_string(), text);
+
+    // Line endings and a missing final newline are kept.
+    for text in ["a\r\n\r\n# b\r\nc\r\n", "a\n\nc", "a\r\nc", "", "\n"] {
+        assert_eq!(TodoList::parse(text).to_string(), text);
+    }
+    let mut list = TodoList::parse("a\r\nb");
+    list.add(Todo::from_str("c").unwrap());
+    assert_eq!(list.to_string(), "a\r\nb\r\nc");
+    list.lines_mut().remove(0);
+    assert_eq!(list.lines().len(), 2);
+}
+
+#[test]
+fn lossless_parsing() {
+    let lines = [
+        "due:2024-01-01 call +mom",
+        "x (A) 2023-01-02 2023-01-01 a:b  spaced   out @home due:today",
+        " leading and trailing ",
+        "2023-1-7 non-canonical date",
+        "rec:1w first:1 first:2 end",
+    ];
+
+    for line in lines {
+        let todo = Todo::parse_lossless(line).unwrap();
+        assert_eq!(todo.to_string(), line);
+    }
+
+    let mut todo = Todo::parse_lossless("due:2024-01-01 call +mom k:v").unwrap();
+    assert_eq!(
+        todo.deadline,
+        TodoDate::Day(
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        )
+    );
+    assert_eq!(todo.get_meta("k"), Some(&"v".to_string()));
+    assert!(todo.has_project_tag("mom"));
+
+    todo.deadline = TodoDate::Always;
+    todo.delete_meta(&"k".to_string());
+    todo.set_meta("new", "meta");
+    assert_eq!(todo.to_string(), "due:0000-00-00 call +mom new:meta");
+
+    todo.deadline = TodoDate::Never;
+    assert_eq!(todo.to_string(), "call +mom new:meta");
+
+    let normal = Todo::from_str("due:2024-01-01 call +mom").unwrap();
+    assert_eq!(normal.to_string(), "call +mom due:2024-01-01");
+}
+
+#[test]
+fn recurrence() {
+    let rec: Recurrence = "+3d".parse().unwrap();
+    assert!(rec.strict);
+    assert_eq!(rec.to_string(), "+3d");
+    assert!("3".parse::<Recurrence>().is_err());
+    assert!("xw".parse::<Recurrence>().is_err());
+
+    let date = |y, m, d| {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    };
+
+    // Friday + 1 business day is Monday
+    let rec: Recurrence = "1b".parse().unwrap();
+    assert_eq!(rec.apply(date(2024, 1, 5)), Some(date(2024, 1, 8)));
+
+    // Business days are counted a week at a time, from any day of the week.
+    for start in 6..13 {
+        let mut expected = date(2024, 1, start);
+        for amount in 0..20 {
+            let rec: Recurrence = format!("{amount}b").parse().unwrap();
+            assert_eq!(rec.apply(date(2024, 1, start)), Some(expected));
+            expected += chrono::Duration::days(1);
+            while matches!(expected.weekday(), Weekday::Sat | Weekday::Sun) {
+                expected += chrono::Duration::days(1);
+            }
+        }
+    }
+    let mut huge = Todo::from_str("Far away due:2024-01-01 rec:4000000000b").unwrap();
+    assert!(huge.complete_recurring().is_none());
+
+    let rec: Recurrence = "1m".parse().unwrap();
+    assert_eq!(rec.apply(date(2024, 1, 31)), Some(date(2024, 2, 29)));
+
+    let rec: Recurrence = "1y".parse().unwrap();
+    assert_eq!(rec.apply(date(2024, 12, 31)), Some(date(2025, 12, 31)));
+
+    let mut todo = Todo::from_str("(A) 2024-01-01 Pay rent due:2024-01-01 rec:+1m").unwrap();
+    let next = todo.complete_recurring().unwrap();
+    assert_eq!(next.deadline, TodoDate::Day(date(2024, 2, 1)));
+    assert_eq!(next.priority, TodoPriority::A);
+    assert!(!next.completed && todo.completed);
+    assert_eq!(next.completion_date, None);
+
+    // Completing it again refreshes the completion date, but doesn't make
+    // another instance.
+    let mut done = Todo::from_str("x 2024-01-02 2024-01-01 Pay rent rec:+1m").unwrap();
+    assert!(done.complete_at(date(2024, 1, 5)).is_none());
+    assert_eq!(done.completion_date, Some(date(2024, 1, 5)));
+    assert!(done.completed);
+
+    let mut todo = Todo::from_str("Stretch due:2024-01-01 rec:2d").unwrap();
+    let next = todo.complete_recurring().unwrap();
+    let today = Local::now().date_naive();
+    assert_eq!(
+        next.deadline,
+        TodoDate::Day(today.and_hms_opt(0, 0, 0).unwrap() + chrono::Days::new(2))
+    );
+    assert_eq!(next.creation, None);
+
+    assert!(Todo::from_str("No rec")
+        .unwrap()
+        .complete_recurring()
+        .is_none());
+}
+
+#[test]
+fn threshold() {
+    let todo = Todo::from_str("Plan trip t:2999-01-01 due:2999-02-01").unwrap();
+    assert!(todo.threshold.is_some());
+    assert!(todo.get_meta("t").is_none());
+    assert!(todo.is_hidden() && !todo.is_actionable());
+    assert_eq!(todo.to_string(), "Plan trip due:2999-02-01 t:2999-01-01");
+
+    let lossless = Todo::parse_lossless("t:2999-01-01 Plan trip").unwrap();
+    assert_eq!(lossless.to_string(), "t:2999-01-01 Plan trip");
+    assert!(lossless.is_hidden());
+
+    let past = Todo::from_str("Book hotel t:2000-01-01").unwrap();
+    assert!(!past.is_hidden() && past.is_actionable());
+
+    let mut table = TodoTable::new(Some("Trip"));
+    table.add_col("Todo");
+    table.add_todo(todo, "Todo");
+    table.add_todo(past, "Todo");
+    table.add_todo(Todo::from_str("x Pack").unwrap(), "Todo");
+
+    assert_eq!(table.iter_visible().count(), 2);
+    assert_eq!(table.iter_actionable().count(), 1);
+
+    let mut rec = Todo::from_str("Review t:2024-01-05 due:2024-01-10 rec:+1w").unwrap();
+    let next = rec.complete_recurring().unwrap();
+    assert_eq!(
+        next.to_string(),
+        "Review due:2024-01-17 t:2024-01-12 rec:+1w"
+    );
+}
+
+#[test]
+fn queries() {
+    use crate::error::QueryError;
+    use crate::query::Query;
+
+    let list = TodoList::parse(
+        "(A) Call Bob +work @phone due:2000-01-01\n\
+         (C) 2024-03-01 Email \"Alice\" +work @computer\n\
+         x (B) Call Carol +home @phone kind:social\n\
+         Plan holiday +home t:2999-01-01\n",
+    );
+
+    let count = |q: &str| list.query(&Query::parse(q).unwrap()).len();
+
+    assert_eq!(count("+work"), 2);
+    assert_eq!(count("+work @phone"), 1);
+    assert_eq!(count("+work or +home"), 4);
+    assert_eq!(count("not done and (@phone or @computer)"), 2);
+    assert_eq!(count("pri>=B"), 2);
+    assert_eq!(count("pri<B"), 2);
+    assert_eq!(count("pri=c"), 1);
+    assert_eq!(count("due"), 1);
+    assert_eq!(count("due<today+7d"), 1);
+    assert_eq!(count("created>=2024-01-01"), 1);
+    assert_eq!(count("hidden"), 1);
+    assert_eq!(count("kind:social"), 1);
+    assert_eq!(count("kind:*"), 1);
+    assert_eq!(count("call"), 2);
+    assert_eq!(count("\"email\" AND NOT call"), 1);
+
+    assert_eq!(Query::parse(""), Err(QueryError::UnexpectedEnd));
+    assert_eq!(Query::parse("(+work"), Err(QueryError::UnclosedParen));
+    assert_eq!(
+        Query::parse("+work)"),
+        Err(QueryError::UnexpectedToken(")".into()))
+    );
+    assert_eq!(
+        Query::parse("and"),
+        Err(QueryError::UnexpectedToken("and".into()))
+    );
+    assert_eq!(
+        Query::parse("pri>1"),
+        Err(QueryError::BadPriority("1".into()))
+    );
+    assert_eq!(
+        Query::parse("due<soon"),
+        Err(QueryError::BadDate("soon".into()))
+    );
+    assert_eq!(Query::parse("\"open"), Err(QueryError::UnclosedQuote));
+}
+
+#[test]
+fn sorting() {
+    use crate::sort::{SortBy, SortKey};
+
+    let mut table = TodoTable::new(Some("Sorted"));
+    table.add_col("A");
+    for line in [
+        "b +zeta",
+        "(C) 2024-01-02 c +alpha due:2024-03-01 size:2",
+        "2024-01-01 A @home size:10",
+        "(A) d due:2024-02-01 size:1",
+        "x 2024-01-05 2024-01-01 e",
+    ] {
+        table.add_todo(Todo::from_str(line).unwrap(), "A");
+    }
+
+    let order = |table: &mut TodoTable| -> String {
+        table
+            .col("A")
+            .unwrap()
+            .iter()
+            .map(|t| {
+                let desc = t.description.to_string();
+                desc.split(' ').next().unwrap().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    table.sort_by_keys(&[SortBy::asc(SortKey::Description)]);
+    assert_eq!(order(&mut table), "A b c d e");
+
+    table.sort_by_keys(&[SortBy::desc(SortKey::Priority)]);
+    assert_eq!(order(&mut table), "c d A b e");
+
+    table.sort_by_keys(&[
+        SortBy::asc(SortKey::Deadline),
+        SortBy::asc(SortKey::Description),
+    ]);
+    assert_eq!(order(&mut table), "d c A b e");
+
+    table.sort_by_keys(&[SortBy::desc(SortKey::Creation)]);
+    assert_eq!(order(&mut table), "c A e d b");
+
+    table.sort_by_keys(&[SortBy::asc(SortKey::Project)]);
+    assert_eq!(order(&mut table), "c b A e d");
+
+    table.sort_by_keys(&[SortBy::asc(SortKey::Meta("size".into()))]);
+    assert_eq!(order(&mut table), "d A c b e");
+
+    table.sort_by_keys(&[SortBy::asc(SortKey::Completion)]);
+    assert_eq!(order(&mut table), "e d A c b");
+}
+
+#[test]
+fn ids() {
+    let mut table = TodoTable::new(Some("Ids"));
+    table.add_col("A");
+    table.add_col("B");
+
+    table.add_todo(Todo::from_str("Same title").unwrap(), "A");
+    table.add_todo(Todo::from_str("Same title id:7").unwrap(), "B");
+    table.assign_ids();
+
+    assert!(table.get_todo_by_id("7").is_some());
+    assert_eq!(
+        table.get_todo_by_id("8").unwrap().to_string(),
+        "Same title id:8"
+    );
+
+    let id = table
+        .add_todo_with_id(Todo::from_str("Third").unwrap(), "B")
+        .unwrap();
+    assert_eq!(id, "9");
+    assert!(table.add_todo_with_id(Todo::default(), "C").is_none());
+
+    // There's nothing above the largest ID, so the lowest free one is used.
+    let mut full = TodoColumn::new("Full");
+    full.add(Todo::from_str("Last id:18446744073709551615").unwrap());
+    full.add(Todo::from_str("First id:1").unwrap());
+    assert_eq!(full.add_with_id(Todo::default()), "2");
+    full.add(Todo::default());
+    full.assign_ids();
+    assert!(full.get_by_id("3").is_some());
+
+    let old = table
+        .update_todo_by_id("7", Todo::from_str("Renamed").unwrap())
+        .unwrap();
+    assert_eq!(old.to_string(), "Same title id:7");
+    assert_eq!(
+        table.get_todo_by_id("7").unwrap().to_string(),
+        "Renamed id:7"
+    );
+
+    assert!(table.move_todo_by_id("7", "A"));
+    assert!(!table.move_todo_by_id("7", "C"));
+    assert!(!table.move_todo_by_id("70", "A"));
+    assert!(table.col("B").unwrap().get_by_id("7").is_none());
+    assert!(table.col("A").unwrap().get_by_id("7").is_some());
+
+    assert!(table.remove_todo_by_id("9").is_some());
+    assert!(table.get_todo_by_id("9").is_none());
+
+    let mut col = TodoColumn::new("Col");
+    assert_eq!(col.add_with_id(Todo::from_str("a").unwrap()), "1");
+    assert_eq!(col.add_with_id(Todo::from_str("b id:x").unwrap()), "x");
+    assert_eq!(col.add_with_id(Todo::from_str("c").unwrap()), "2");
+    assert!(col.remove_by_id("x").is_some());
+
+    let mut rec = Todo::from_str("Water plants rec:1w id:3").unwrap();
+    assert!(rec.complete_recurring().unwrap().id().is_none());
+
+    // Titles match however their words are spaced.
+    let mut table = TodoTable::new(Some("Spaced"));
+    table.add_col("A");
+    table.add_todo(Todo::parse_lossless("Call  mom").unwrap(), "A");
+    assert!(table.get_todo("Call mom", "A").is_some());
+    assert!(table.get_todo("Call", "A").is_none());
+    assert!(table.col("A").unwrap().pop("Call mom").is_some());
+}
+
+#[test]
+fn dependencies() {
+    use crate::deps::DependencyGraph;
+
+    let mut todo = Todo::from_str("Deploy dep:1").unwrap();
+    todo.add_dependency("2");
+    todo.add_dependency("1");
+    assert_eq!(todo.dependencies(), ["1", "2"]);
+    assert_eq!(todo.to_string(), "Deploy dep:1,2");
+
+    let mut col = TodoColumn::new("Todo");
+    col.add(Todo::from_str("c id:c dep:b,zzz").unwrap());
+    col.add(Todo::from_str("b id:b").unwrap());
+    col.add(Todo::from_str("a id:a blocks:b").unwrap());
+    col.add(Todo::from_str("free").unwrap());
+
+    let graph = DependencyGraph::new(col.iter());
+    let names = |todos: Vec<&Todo>| {
+        todos
+            .iter()
+            .map(|t| t.description.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(graph.ready()), ["a", "free"]);
+    assert_eq!(names(graph.blocked()), ["c", "b"]);
+    assert!(graph.is_blocked("b") && !graph.is_blocked("a"));
+    assert_eq!(names(graph.dependencies_of("c")), ["b"]);
+    assert_eq!(graph.missing().len(), 1);
+    assert_eq!(graph.missing()[0].1, "zzz");
+    assert!(graph.find_cycle().is_none());
+    assert_eq!(
+        names(graph.topological_order().unwrap()),
+        ["a", "b", "c", "free"]
+    );
+
+    col.add(Todo::from_str("d id:d dep:e").unwrap());
+    col.add(Todo::from_str("e id:e dep:c blocks:b").unwrap());
+    let graph = DependencyGraph::new(col.iter());
+    assert!(graph.topological_order().is_none());
+
+    let cycle = names(graph.find_cycle().unwrap());
+    assert_eq!(cycle.len(), 3);
+    assert!(["b", "c", "e"]
+        .iter()
+        .all(|n| cycle.contains(&n.to_string())));
+}
+
+#[test]
+fn subtasks() {
+    let mut col = TodoColumn::new("Move");
+    for line in [
+        "Move house id:1",
+        "Pack id:2 parent:1",
+        "Kitchen id:3 parent:2",
+        "Other",
+        "Book van id:4 parent:1",
+        "Orphan parent:99",
+    ] {
+        col.add(Todo::from_str(line).unwrap());
+    }
+
+    assert_eq!(col.children("1").len(), 2);
+    let depths: Vec<usize> = col.subtree("1").iter().map(|(d, _)| *d).collect();
+    assert_eq!(depths, [0, 1, 2, 1]);
+
+    assert_eq!(col.completion_percentage("1"), Some(0.0));
+    assert_eq!(col.completion_percentage("3"), None);
+
+    assert!(col.complete_by_id("3", true));
+    assert!(col.get_by_id("2").unwrap().completed);
+    assert!(!col.get_by_id("1").unwrap().completed);
+    assert_eq!(col.completion_percentage("1"), Some(50.0));
+    assert_eq!(col.completion_percentage("2"), Some(100.0));
+
+    assert!(col.complete_by_id("4", false));
+    assert!(!col.get_by_id("1").unwrap().completed);
+    assert!(!col.complete_by_id("404", true));
+
+    assert_eq!(
+        col.to_string(),
+        "| Move |\n\
+         | Move house id:1\n\
+         |   x Pack id:2 parent:1\n\
+         |     x Kitchen id:3 parent:2\n\
+         |   x Book van id:4 parent:1\n\
+         | Other\n\
+         | Orphan parent:99\n"
+    );
+
+    // Deep chains are walked without recursing.
+    let mut col = TodoColumn::new("Chain");
+    col.add(Todo::from_str("Root id:0").unwrap());
+    for i in 1..100_000 {
+        col.add(Todo::from_str(&format!("Step id:{i} parent:{}", i - 1)).unwrap());
+    }
+    let depths: Vec<usize> = col.subtree("0").iter().map(|(d, _)| *d).collect();
+    assert_eq!(depths.len(), 100_000);
+    assert_eq!(depths.last(), Some(&99_999));
+    assert_eq!(col.completion_percentage("99998"), Some(0.0));
+
+    // Next instances get IDs unique across the table, as of the given time.
+    let now = NaiveDate::from_ymd_opt(2024, 5, 6)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut table = TodoTable::new(Some("Chores"));
+    table.add_col("Home");
+    table.add_col("Garden");
+    table.add_todo(Todo::from_str("Clean id:1 rec:1w").unwrap(), "Home");
+    table.add_todo(Todo::from_str("Water id:2").unwrap(), "Garden");
+    assert!(table.complete_todo_by_id_at("1", true, now));
+    assert_eq!(
+        table.col("Home").unwrap().todos[1].to_string(),
+        "Clean due:2024-05-13 rec:1w id:3"
+    );
+    assert_eq!(table.get_todo_by_id("2").unwrap().to_string(), "Water id:2");
+    assert_eq!(
+        table.get_todo_by_id("1").unwrap().completion_date,
+        Some(now)
+    );
+    assert!(!table.complete_todo_by_id("404", true));
+}
+
+#[test]
+fn archiving() {
+    use crate::archive::{archive_file, ArchiveOptions};
+
+    let today = Local::now().date_naive();
+    let ago = |days| (today - chrono::Days::new(days)).format("%F").to_string();
+
+    let text = format!(
+        "# header\n\
+         x {} 2000-01-01 recent\n\
+         open\n\
+         x {} 2000-01-01 old\n\
+         x undated\n\
+         x {} 2000-01-01 older\n",
+        ago(1),
+        ago(10),
+        ago(20),
+    );
+
+    let mut list = TodoList::parse(&text);
+    let mut done = TodoList::new();
+    let options = ArchiveOptions::new()
+        .keep_days(Some(5))
+        .preserve_order(false);
+
+    assert_eq!(list.archive(&mut done, options), 3);
+    assert_eq!(
+        list.to_string(),
+        format!("# header\nx {} 2000-01-01 recent\nopen\n", ago(1))
+    );
+
+    let archived: Vec<_> = done.iter().map(|t| t.description.to_string()).collect();
+    assert_eq!(archived, ["older", "old", "undated"]);
+
+    let mut col = TodoColumn::new("Todo");
+    col.add(Todo::from_str("x one").unwrap());
+    col.add(Todo::from_str("two").unwrap());
+    let mut done = TodoColumn::new("Done");
+    assert_eq!(col.archive(&mut done, ArchiveOptions::new()), 1);
+    assert_eq!(col.iter().count(), 1);
+    assert_eq!(done.iter().count(), 1);
+
+    let dir = std::env::temp_dir().join(format!("todo-lib-archive-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (todo_path, done_path) = (dir.join("todo.txt"), dir.join("done.txt"));
+
+    std::fs::write(&todo_path, "x a\nb\n").unwrap();
+    let _ = std::fs::remove_file(&done_path);
+    assert_eq!(
+        archive_file(&todo_path, &done_path, ArchiveOptions::new()).unwrap(),
+        1
+    );
+    std::fs::write(&todo_path, "x c\n").unwrap();
+    assert_eq!(
+        archive_file(&todo_path, &done_path, ArchiveOptions::new()).unwrap(),
+        1
+    );
+
+    assert_eq!(std::fs::read_to_string(&todo_path).unwrap(), "");
+    assert_eq!(std::fs::read_to_string(&done_path).unwrap(), "x a\nx c\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_roundtrip() {
+    let mut table = TodoTable::new(Some("Serde"));
+    table.add_col("A");
+    table.add_todo(
+        Todo::from_str("(A) 2024-01-01 Call +mom @phone due:2024-02-01").unwrap(),
+        "A",
+    );
+    table.add_todo(Todo::from_str("x Done a:1 b:2").unwrap(), "A");
+
+    let json = serde_json::to_string(&table).unwrap();
+    let back: TodoTable = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, table);
+
+    // In-place tokens aren't part of the schema; their values are.
+    let todo = Todo::parse_lossless("Call due:2024-02-01 k:v t:2024-01-15 mom").unwrap();
+    let value = serde_json::to_value(&todo).unwrap();
+    assert_eq!(
+        value["description"],
+        serde_json::json!([{"string": "Call"}, {"string": "mom"}])
+    );
+    assert_eq!(value["metadata"], serde_json::json!({"k": "v"}));
+    assert_eq!(value["deadline"]["day"], "2024-02-01T00:00:00");
+    assert_eq!(value["threshold"], "2024-01-15T00:00:00");
+    let back: Todo = serde_json::from_value(value).unwrap();
+    assert_eq!(back, Todo::from_str(&todo.to_string()).unwrap());
+    assert_eq!(back.to_string(), "Call mom due:2024-02-01 t:2024-01-15 k:v");
+
+    let todo = Todo::from_str("(B) Task due:2024-01-01 z:1 a:2").unwrap();
+    let value = serde_json::to_value(&todo).unwrap();
+    assert_eq!(value["priority"], "B");
+    assert_eq!(value["deadline"]["day"], "2024-01-01T00:00:00");
+    assert_eq!(value["metadata"], serde_json::json!({"z": "1", "a": "2"}));
+    assert_eq!(
+        serde_json::to_string(&todo.metadata).unwrap(),
+        r#"{"z":"1","a":"2"}"#
+    );
+    assert_eq!(
+        value["description"],
+        serde_json::json!([{"string": "Task"}])
+    );
+
+    let partial: Todo = serde_json::from_str(r#"{"completed": true}"#).unwrap();
+    assert!(partial.completed);
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Lines {
+        #[serde(with = "crate::serialize::todo_txt_seq")]
+        todos: Vec<Todo>,
+    }
+
+    let json = r#"{"todos":["due:2024-01-01 first","x second"]}"#;
+    let lines: Lines = serde_json::from_str(json).unwrap();
+    assert_eq!(lines.todos.len(), 2);
+    assert_eq!(serde_json::to_string(&lines).unwrap(), json);
+}
+
+#[test]
+fn table_persistence() {
+    use crate::error::TableParseError;
+
+    let mut table = TodoTable::new(Some("Kanban"));
+    table.add_col("Backlog");
+    table.add_col("Doing");
+    table.add_col("Done");
+    table.add_todo(Todo::from_str("(B) Write spec +app").unwrap(), "Backlog");
+    table.add_todo(Todo::from_str("Design due:2024-01-01").unwrap(), "Doing");
+    table.add_todo(Todo::from_str("Code id:2").unwrap(), "Doing");
+
+    let text = table.to_todo_txt();
+    assert_eq!(
+        text,
+        "# table: Kanban\n\n\
+         # column: Backlog\n(B) Write spec +app\n\n\
+         # column: Doing\nDesign due:2024-01-01\nCode id:2\n\n\
+         # column: Done\n"
+    );
+
+    let path = std::env::temp_dir().join(format!("todo-lib-table-{}.txt", std::process::id()));
+    table.save(&path).unwrap();
+    let loaded = TodoTable::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.to_todo_txt(), text);
+    assert_eq!(loaded.title(), "Kanban");
+    let titles: Vec<_> = loaded.columns().iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["Backlog", "Doing", "Done"]);
+
+    assert!(matches!(
+        TodoTable::parse_todo_txt("# table: T\n# note\nstray\n"),
+        Err(TableParseError::MissingColumn(3))
+    ));
+    // Bad todos are reported, but kept as they were.
+    let text = "# table: T\n\n# column: A\nok\nbad due:xd\n";
+    let loaded = TodoTable::parse_todo_txt(text).unwrap();
+    assert_eq!(loaded.errors().len(), 1);
+    assert_eq!(loaded.errors()[0].line, 5);
+    assert_eq!(loaded.to_todo_txt(), text);
+
+    let err = TodoTable::read("stray".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Todos which would read as comments or blank lines are marked.
+    let mut bugs = TodoTable::new(Some("Bugs"));
+    bugs.add_col("Open");
+    for todo in ["#1 priority bug", " #2 indented", "", "  "] {
+        bugs.add_todo(Todo::parse_lossless(todo).unwrap(), "Open");
+    }
+    let text = bugs.to_todo_txt();
+    assert_eq!(
+        text,
+        "# table: Bugs\n\n# column: Open\n# todo: #1 priority bug\n \
+         #2 indented\n# todo: \n# todo:   \n"
+    );
+    let loaded = TodoTable::parse_todo_txt(&text).unwrap();
+    assert!(loaded.errors().is_empty());
+    let todos: Vec<_> = loaded.iter().map(|t| t.to_string()).collect();
+    assert_eq!(todos, ["#1 priority bug", " #2 indented", "", "  "]);
+    assert_eq!(loaded.to_todo_txt(), text);
+}
+
+#[test]
+fn ical_roundtrip() {
+    use crate::error::ICalError;
+    use crate::ical;
+
+    let mut todo = Todo::from_str(
+        "(K) 2024-01-01 Reply, then file; maybe +mail @desk due:2024-02-01 t:2024-01-20 uid:abc rel:work",
+    )
+    .unwrap();
+    let ics = ical::to_ics([&todo]);
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.contains("UID:abc\r\n"));
+    assert!(ics.contains("SUMMARY:Reply\\, then file\\; maybe +mail @desk\r\n"));
+    assert!(ics.contains("PRIORITY:9\r\n"));
+    assert!(ics.contains("DTSTART;VALUE=DATE:20240101\r\n"));
+    assert!(ics.contains("X-TODOTXT-THRESHOLD;VALUE=DATE:20240120\r\n"));
+    assert!(ics.contains("X-TODOTXT-META:rel:work\r\n"));
+    assert!(ics.lines().all(|line| line.len() <= 75));
+
+    let imported = ical::from_vtodo(&ics).unwrap();
+    todo.priority = TodoPriority::I;
+    assert_eq!(imported.to_string(), todo.to_string());
+
+    todo.complete();
+    let imported = ical::from_vtodo(ical::to_vtodo(&todo)).unwrap();
+    assert!(imported.completed);
+    assert_eq!(
+        imported.completion_date.unwrap().date(),
+        todo.completion_date.unwrap().date()
+    );
+
+    let long = Todo::from_str(&"word ".repeat(40)).unwrap();
+    let folded = ical::to_vtodo(&long);
+    assert!(folded.contains("\r\n "));
+    assert_eq!(
+        ical::from_vtodo(folded).unwrap().description,
+        long.description
+    );
+
+    let foreign = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Meeting\nEND:VEVENT\n\
+                   BEGIN:VTODO\nSUMMARY:Buy milk\nCATEGORIES:Errands,+home\n\
+                   PRIORITY:0\nDUE:20240301T090000\nBEGIN:VALARM\nACTION:DISPLAY\n\
+                   END:VALARM\nEND:VTODO\nEND:VCALENDAR\n";
+    let todos = ical::parse_ics(foreign).unwrap();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(
+        todos[0].to_string(),
+        "Buy milk @Errands +home due:2024-03-01T09:00"
+    );
+    assert_eq!(
+        todos[0].deadline,
+        TodoDate::Timed(
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        )
+    );
+
+    // Generated UIDs don't depend on the Rust release.
+    let plants = Todo::from_str("2024-01-01 Water plants").unwrap();
+    assert!(ical::to_vtodo(&plants).contains("UID:9baf3c909889af26@todo-lib\r\n"));
+
+    // Identical todos get distinct UIDs, which assign_uids keeps.
+    let mut twins = vec![plants.clone(), plants.clone()];
+    let ics = ical::to_ics(&twins);
+    let uids: Vec<_> = ics
+        .lines()
+        .filter(|line| line.starts_with("UID:"))
+        .collect();
+    assert_eq!(uids.len(), 2);
+    assert_eq!(uids[0], "UID:9baf3c909889af26@todo-lib");
+    assert_ne!(uids[0], uids[1]);
+    ical::assign_uids(&mut twins);
+    assert_ne!(twins[0].get_meta("uid"), twins[1].get_meta("uid"));
+    twins.reverse();
+    let reordered = ical::to_ics(&twins);
+    assert!(uids.iter().all(|uid| reordered.contains(uid)));
+
+    // Exports are stamped with the given time, so they're reproducible.
+    let now = NaiveDate::from_ymd_opt(2024, 5, 6)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    assert_eq!(
+        ical::to_ics_at([&plants], now),
+        ical::to_ics_at([&plants], now)
+    );
+    assert!(
+        ical::to_vtodo_at(&plants, now).contains(&format!("DTSTAMP:{}\r\n", ical::format_utc(now)))
+    );
+
+    // UIDs which aren't valid metadata are kept, percent-encoded.
+    let foreign = ical::from_vtodo("BEGIN:VTODO\nUID:urn:uuid:1 2\nEND:VTODO").unwrap();
+    assert_eq!(
+        foreign.get_meta("uid"),
+        Some(&"urn%3Auuid%3A1%202".to_string())
+    );
+    assert!(ical::to_vtodo(&foreign).contains("UID:urn:uuid:1 2\r\n"));
+
+    let split = Todo::from_str("Carriage\rreturn").unwrap();
+    assert!(ical::to_vtodo(&split).contains("SUMMARY:Carriage\\nreturn\r\n"));
+
+    // UTC deadlines stay in UTC.
+    let call = Todo::from_str("Call Tokyo due:2024-03-01T09:00+09:00").unwrap();
+    let vtodo = ical::to_vtodo(&call);
+    assert!(vtodo.contains("DUE:20240301T000000Z\r\n"));
+    let imported = ical::from_vtodo(vtodo).unwrap();
+    assert_eq!(imported.deadline, call.deadline);
+    assert_eq!(imported.deadline.value(), "2024-03-01T00:00Z");
+
+    assert_eq!(
+        ical::parse_ics("BEGIN:VTODO\nDUE:soon\nEND:VTODO"),
+        Err(ICalError::BadDate("soon".to_string()))
+    );
+    assert_eq!(
+        ical::parse_ics("BEGIN:VTODO\nSUMMARY:x"),
+        Err(ICalError::Unterminated("VTODO".to_string()))
+    );
+    assert_eq!(ical::from_vtodo(""), Err(ICalError::MissingTodo));
+}
+
+#[cfg(feature = "taskwarrior")]
+#[test]
+fn taskwarrior_roundtrip() {
+    use crate::error::TaskwarriorError;
+    use crate::taskwarrior;
+
+    let json = r#"[
+{"id":1,"uuid":"a1","description":"Write report","entry":"20240101T000000Z","due":"20240201T120000Z","project":"work","tags":["office","urgent"],"priority":"M","status":"pending","urgency":5.2,"estimate":3,"depends":["b2"]},
+{"id":0,"uuid":"b2","description":"Book flight","entry":"20240101T000000Z","end":"20240105T000000Z","priority":"H","status":"completed","note":"window seat: yes"},
+{"id":0,"uuid":"c3","description":"Old idea","status":"deleted"}
+]"#;
+
+    let todos = taskwarrior::parse_export(json).unwrap();
+    assert_eq!(todos.len(), 3);
+
+    let report = &todos[0];
+    assert!(report.has_project_tag("work"));
+    assert!(report.has_context_tag("office") && report.has_context_tag("urgent"));
+    assert_eq!(report.priority, TodoPriority::B);
+    assert!(report.deadline.is_some() && report.creation.is_some());
+    assert_eq!(report.get_meta("uuid"), Some(&"a1".to_string()));
+    assert_eq!(report.get_meta("estimate"), Some(&"=3".to_string()));
+    assert_eq!(report.get_meta("id"), None);
+    assert_eq!(report.get_meta("urgency"), None);
+
+    assert_eq!(
+        report.deadline,
+        TodoDate::Zoned(
+            Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0)
+                .unwrap()
+                .fixed_offset()
+        )
+    );
+
+    let flight = &todos[1];
+    assert!(flight.completed && flight.completion_date.is_some());
+    assert_eq!(flight.get_meta("pri"), Some(&"A".to_string()));
+    assert_eq!(
+        flight.get_meta("note"),
+        Some(&"window%20seat%3A%20yes".to_string())
+    );
+    assert_eq!(todos[2].get_meta("status"), Some(&"deleted".to_string()));
+
+    let tasks: Vec<serde_json::Value> =
+        serde_json::from_str(&taskwarrior::to_export(&todos)).unwrap();
+    let expected: Vec<serde_json::Value> = serde_json::from_str(json).unwrap();
+    for (task, mut expected) in tasks.into_iter().zip(expected) {
+        for key in ["id", "urgency"] {
+            expected.as_object_mut().unwrap().remove(key);
+        }
+        assert_eq!(task, expected);
+    }
+
+    let milk = taskwarrior::from_task(&serde_json::json!({
+        "description": "Buy +milk @store",
+        "project": "home"
+    }))
+    .unwrap();
+    assert!(milk.has_project_tag("home") && !milk.has_project_tag("milk"));
+    let task = taskwarrior::to_task(&milk);
+    assert_eq!(task["description"], "Buy +milk @store");
+    assert_eq!(task["project"], "home");
+    assert_eq!(task.get("tags"), None);
+
+    // todo.txt IDs don't clash with Taskwarrior's.
+    let deploy = Todo::from_str("Deploy id:5 dep:4").unwrap();
+    let task = taskwarrior::to_task(&deploy);
+    assert_eq!(task["todotxt_id"], "5");
+    assert_eq!(task["dep"], "4");
+    let imported = taskwarrior::from_task(&task).unwrap();
+    assert_eq!(imported.id(), Some(&"5".to_string()));
+    assert_eq!(imported.to_string(), deploy.to_string());
+
+    // String UDAs keep their type, even if they read as JSON.
+    let uda = serde_json::json!({
+        "description": "Fix",
+        "status": "pending",
+        "ticket": "123",
+        "flag": "true",
+        "formula": "=1+1",
+        "points": 2,
+        "done": false
+    });
+    let todo = taskwarrior::from_task(&uda).unwrap();
+    assert_eq!(
+        todo.to_string(),
+        "Fix done:=false flag:true formula:%3D1+1 points:=2 ticket:123"
+    );
+    assert_eq!(taskwarrior::to_task(&todo), uda);
+
+    let table = taskwarrior::parse_table(json, Some("Tasks")).unwrap();
+    let titles: Vec<_> = table.columns().iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["pending", "completed", "deleted"]);
+
+    assert!(matches!(
+        taskwarrior::parse_export("{}"),
+        Err(TaskwarriorError::Json(_))
+    ));
+    assert_eq!(
+        taskwarrior::parse_export(r#"[{"priority":"X"}]"#),
+        Err(TaskwarriorError::BadPriority("X".to_string()))
+    );
+    assert_eq!(
+        taskwarrior::parse_export(r#"[{"due":"tomorrow"}]"#),
+        Err(TaskwarriorError::BadDate("tomorrow".to_string()))
+    );
+}
+
+#[test]
+fn markdown_roundtrip() {
+    use crate::error::TableParseError;
+    use crate::markdown;
+
+    let mut table = TodoTable::new(Some("Project"));
+    table.add_col("Todo");
+    table.add_col("Done");
+    table.add_todo(Todo::from_str("(A) Plan +launch id:1").unwrap(), "Todo");
+    table.add_todo(
+        Todo::from_str("Draft post @blog parent:1 due:2024-02-01").unwrap(),
+        "Todo",
+    );
+    table.add_todo(Todo::from_str("x 2024-01-02 Kickoff").unwrap(), "Done");
+
+    let text = markdown::table_to_markdown(&table);
+    assert_eq!(
+        text,
+        "# Project\n\n\
+         ## Todo\n\n\
+         - [ ] (A) Plan +launch id:1\n  \
+         - [ ] Draft post @blog due:2024-02-01 parent:1\n\n\
+         ## Done\n\n\
+         - [x] 2024-01-02 Kickoff\n"
+    );
+
+    let parsed = markdown::parse_markdown(&text).unwrap();
+    assert_eq!(markdown::table_to_markdown(&parsed), text);
+    assert_eq!(
+        parsed.columns()[0].todos[1].parent(),
+        Some(&"1".to_string())
+    );
+    assert!(parsed.columns()[1].todos[0].completed);
+
+    // The checkbox decides completion, not a leading x.
+    let mut marks = TodoColumn::new("Col");
+    marks.add(Todo::from_str("x x marks").unwrap());
+    marks.todos[0].completed = false;
+    marks.add(Todo::from_str("x x spot").unwrap());
+    marks.add(Todo::from_str("\\x foo").unwrap());
+    let text = markdown::column_to_markdown(&marks);
+    assert_eq!(
+        text,
+        "## Col\n\n- [ ] \\x marks\n- [x] x spot\n- [ ] \\\\x foo\n"
+    );
+    let parsed = markdown::parse_markdown(&text).unwrap();
+    let todos: Vec<_> = parsed
+        .iter()
+        .map(|t| (t.completed, t.to_string()))
+        .collect();
+    assert_eq!(
+        todos,
+        [
+            (false, "x marks".to_string()),
+            (true, "x x spot".to_string()),
+            (false, "\\x foo".to_string())
+        ]
+    );
+    assert_eq!(markdown::column_to_markdown(&parsed.columns()[0]), text);
+
+    let notes = "Intro text\n\n## Col\n\n* [X] done item\n- plain note\n\
+                 ```\n- [ ] not a todo\n```\n### Details\n+ [ ] (B) real @home\n";
+    let parsed = markdown::parse_markdown(notes).unwrap();
+    assert_eq!(parsed.title(), "Todos");
+    let todos: Vec<_> = parsed.iter().map(|t| t.to_string()).collect();
+    assert_eq!(todos, ["x done item", "(B) real @home"]);
+
+    assert!(matches!(
+        markdown::parse_markdown("# T\n- [ ] stray\n"),
+        Err(TableParseError::MissingColumn(2))
+    ));
+
+    // Bad items don't fail the whole document.
+    let bad = markdown::parse_markdown("## C\n- [ ] bad due:xd\n- [ ] good\n").unwrap();
+    assert_eq!(bad.errors().len(), 1);
+    assert_eq!(bad.errors()[0].line, 2);
+    let todos: Vec<_> = bad.iter().map(|t| t.to_string()).collect();
+    assert_eq!(todos, ["bad due:xd", "good"]);
+}
+
+#[test]
+fn org_roundtrip() {
+    use crate::error::TableParseError;
+    use crate::org;
+
+    let mut table = TodoTable::new(Some("Launch"));
+    table.add_col("Todo");
+    table.add_col("Done");
+    table.add_todo(
+        Todo::from_str("(A) 2024-01-01 Plan +launch @office id:1 due:2024-02-01 t:2024-01-15")
+            .unwrap(),
+        "Todo",
+    );
+    table.add_todo(
+        Todo::from_str("Draft post +blog-posts +@weird parent:1").unwrap(),
+        "Todo",
+    );
+    table.add_todo(
+        Todo::from_str("x 2024-01-03 2024-01-02 Kickoff @meeting").unwrap(),
+        "Done",
+    );
+
+    let text = org::table_to_org(&table);
+    assert_eq!(
+        text,
+        "#+TITLE: Launch\n\
+         * Todo\n\
+         ** TODO [#A] Plan :launch:@office:\n\
+         DEADLINE: <2024-02-01 Thu> SCHEDULED: <2024-01-15 Mon>\n\
+         :PROPERTIES:\n:CREATED: [2024-01-01 Mon]\n:id: 1\n:END:\n\
+         *** TODO Draft post +blog-posts +@weird\n\
+         * Done\n\
+         ** DONE Kickoff :@meeting:\n\
+         CLOSED: [2024-01-03 Wed]\n\
+         :PROPERTIES:\n:CREATED: [2024-01-02 Tue]\n:END:\n"
+    );
+
+    let parsed = org::parse_org(&text).unwrap();
+    assert_eq!(org::table_to_org(&parsed), text);
+    let todos: Vec<_> = parsed.iter().map(|t| t.to_string()).collect();
+    assert_eq!(
+        todos,
+        [
+            "(A) 2024-01-01 Plan +launch @office due:2024-02-01 t:2024-01-15 id:1",
+            "Draft post +blog-posts +@weird parent:1",
+            "x 2024-01-03 2024-01-02 Kickoff @meeting",
+        ]
+    );
+
+    let notes = "#+TITLEé\n#+title: Notes\nIntro\n* Col\n** Outline\nSome notes.\n\
+                 *** TODO [#C] Step one :@home:\n\
+                 DEADLINE: <2024-03-01 Fri 09:30 +1w>\n\
+                 :PROPERTIES:\n:EFFORT: 1:30\n:owner: sam\n:END:\n\
+                 *** DONE Step two\n";
+    let parsed = org::parse_org(notes).unwrap();
+    assert_eq!(parsed.title(), "Notes");
+    let todos: Vec<_> = parsed.iter().map(|t| t.to_string()).collect();
+    assert_eq!(
+        todos,
+        [
+            "Outline id:1",
+            "(C) Step one @home due:2024-03-01T09:30 owner:sam parent:1",
+            "x Step two parent:1",
+        ]
+    );
+    assert_eq!(
+        parsed.columns()[0].todos[1].deadline,
+        TodoDate::Timed(
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap()
+        )
+    );
+
+    assert!(matches!(
+        org::parse_org("** TODO stray\n"),
+        Err(TableParseError::MissingColumn(1))
+    ));
+    assert!(matches!(
+        org::parse_org("* C\n** TODO bad\nDEADLINE: <someday>\n"),
+        Err(TableParseError::Todo(_))
+    ));
+}
+
+#[test]
+fn csv_roundtrip() {
+    use crate::csv;
+    use crate::error::CsvError;
+
+    let mut table = TodoTable::new(Some("Backlog"));
+    table.add_col("Todo");
+    table.add_col("Done");
+    table.add_todo(
+        Todo::from_str("(B) 2024-01-01 Say \"hi\" +app +web @team t:2024-01-10 id:1").unwrap(),
+        "Todo",
+    );
+    let mut fix = Todo::from_str("Fix\tbug owner:sam column:web").unwrap();
+    fix.deadline = TodoDate::Always;
+    table.add_todo(fix, "Todo");
+    table.add_todo(
+        Todo::from_str("x 2024-01-03 2024-01-02 Release id:2").unwrap(),
+        "Done",
+    );
+
+    let text = csv::table_to_csv(&table, csv::TAB);
+    assert_eq!(
+        text,
+        "column\tcompleted\tpriority\tcreation\tcompletion\tdue\tthreshold\tdescription\tprojects\tcontexts\tid\towner\tmeta:column\n\
+         Todo\tfalse\tB\t2024-01-01\t\t\t2024-01-10\t\"Say \"\"hi\"\"\"\tapp web\tteam\t1\t\t\n\
+         Todo\tfalse\t\t\t\t0000-00-00\t\t\"Fix\tbug\"\t\t\t\tsam\tweb\n\
+         Done\ttrue\t\t2024-01-02\t2024-01-03\t\t\tRelease\t\t\t2\t\t\n"
+    );
+
+    let parsed = csv::parse_csv(&text, csv::TAB).unwrap();
+    assert_eq!(csv::table_to_csv(&parsed, csv::TAB), text);
+    let titles: Vec<_> = parsed.columns().iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["Todo", "Done"]);
+    for (a, b) in parsed.iter().zip(table.iter()) {
+        assert_eq!(a.to_string(), b.to_string());
+    }
+
+    let sheet = "description,Priority,completed,due,contexts\r\n\
+                 \"Two\nlines\",,x,2024-05-01,home\r\n\
+                 \r\n\
+                 Short\r\n";
+    let parsed = csv::parse_csv(sheet, csv::COMMA).unwrap();
+    assert_eq!(parsed.columns()[0].title, "Todos");
+    let todos: Vec<_> = parsed.iter().map(|t| t.to_string()).collect();
+    assert_eq!(todos, ["x Two\nlines @home due:2024-05-01", "Short"]);
+
+    // Fields a spreadsheet would run as formulas can be escaped.
+    let mut sheet = TodoTable::new(Some("Sheet"));
+    sheet.add_col("=cmd");
+    sheet.add_todo(
+        Todo::from_str("=HYPERLINK(\"x\") 'quoted +@at delta:-5").unwrap(),
+        "=cmd",
+    );
+    let options = csv::CsvOptions::new(csv::COMMA).escape_formulas(true);
+    let text = csv::table_to_csv(&sheet, options);
+    assert_eq!(
+        text.lines().nth(1),
+        Some("'=cmd,false,,,,,,\"'=HYPERLINK(\"\"x\"\") 'quoted\",'@at,,'-5")
+    );
+    let parsed = csv::parse_csv(&text, options).unwrap();
+    assert_eq!(csv::table_to_csv(&parsed, options), text);
+    assert_eq!(parsed.columns()[0].title, "=cmd");
+    assert_eq!(
+        parsed.iter().next().unwrap().to_string(),
+        sheet.iter().next().unwrap().to_string()
+    );
+    assert!(csv::table_to_csv(&sheet, csv::COMMA).contains("\n=cmd,"));
+
+    assert_eq!(csv::parse_csv("", csv::COMMA), Err(CsvError::MissingHeader));
+    assert_eq!(
+        csv::parse_csv("description\n\"open", csv::COMMA),
+        Err(CsvError::UnclosedQuote(2))
+    );
+    assert_eq!(
+        csv::parse_csv("description,due\nA,\nB,soon", csv::COMMA),
+        Err(CsvError::BadValue {
+            line: 3,
+            column: "due".to_string(),
+            value: "soon".to_string()
+        })
+    );
+}
+
+#[test]
+fn html_rendering() {
+    use crate::html;
+
+    let todo =
+        Todo::from_str("x 2024-01-02 2024-01-01 Say \"hi\" & 'bye' @home due:2020-01-01 k:v")
+            .unwrap();
+    assert_eq!(
+        html::todo_to_html(&todo),
+        "<span class=\"todo todo-completed\">\
+         <span class=\"todo-tick\">x</span> \
+         <span class=\"todo-completion\">2024-01-02</span> \
+         <span class=\"todo-creation\">2024-01-01</span> \
+         <span class=\"todo-description\">Say</span> \
+         <span class=\"todo-description\">&quot;hi&quot;</span> \
+         <span class=\"todo-description\">&amp;</span> \
+         <span class=\"todo-description\">&#39;bye&#39;</span> \
+         <span class=\"todo-context\">@home</span> \
+         <span class=\"todo-deadline\">due:2020-01-01</span> \
+         <span class=\"todo-metadata\">k:v</span>\
+         </span>"
+    );
+
+    let due = Todo::from_str("Pay due:2020-01-01 t:2020-01-01").unwrap();
+    assert!(html::todo_to_html(&due).starts_with("<span class=\"todo todo-due\">"));
+    assert!(html::todo_to_html(&due).contains("<span class=\"todo-threshold\">t:2020-01-01</span>"));
+
+    // Classes depend on the renderer's time, not the wall clock.
+    let before = NaiveDate::from_ymd_opt(2019, 12, 31)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    let upcoming = due.render(&html::Html::at(before));
+    assert!(upcoming.starts_with("<span class=\"todo todo-hidden\">"));
+
+    let mut table = TodoTable::new(Some("<Status>"));
+    table.add_col("Doing");
+    table.add_todo(Todo::from_str("Parent id:1").unwrap(), "Doing");
+    table.add_todo(Todo::from_str("Child id:2 parent:1").unwrap(), "Doing");
+    table.add_todo(Todo::from_str("Grandchild parent:2").unwrap(), "Doing");
+    table.add_todo(Todo::from_str("Sibling").unwrap(), "Doing");
+
+    let tag = |todo: &str| {
+        let todo = Todo::from_str(todo).unwrap();
+        html::todo_to_html(&todo)
+    };
+    assert_eq!(
+        html::table_to_html(&table),
+        format!(
+            "<div class=\"todo-table\">\n<h1>&lt;Status&gt;</h1>\n\
+             <section class=\"todo-column\">\n<h2>Doing</h2>\n<ul>\n\
+             <li>{}\n<ul>\n<li>{}\n<ul>\n<li>{}</li>\n</ul></li>\n</ul></li>\n\
+             <li>{}</li>\n</ul>\n</section>\n</div>\n",
+            tag("Parent id:1"),
+            tag("Child id:2 parent:1"),
+            tag("Grandchild parent:2"),
+            tag("Sibling"),
+        )
+    );
+
+    let page = html::page(&table);
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<title>&lt;Status&gt;</title>"));
+    assert!(page.contains(html::DEFAULT_CSS));
+}
+
+#[test]
+fn custom_renderer() {
+    use crate::colors::{StyleScheme, RESET};
+    use crate::render::{Ansi, Element, Plain};
+
+    /// Wraps every element in brackets, and lists todos by depth.
+    struct Brackets;
+
+    impl Renderer for Brackets {
+        fn element(&self, _: &Todo, element: Element, text: &str) -> String {
+            format!("[{element:?} {text}]")
+        }
+
+        fn column(&self, col: &TodoColumn, todos: Vec<(usize, String)>) -> String {
+            let todos: Vec<String> = todos
+                .into_iter()
+                .map(|(depth, todo)| format!("{depth}{todo}"))
+                .collect();
+            format!("{}: {}", col.title, todos.join(", "))
+        }
+    }
+
+    let todo = Todo::from_str("(A) 2024-01-01 Call +mom @phone due:2024-01-02").unwrap();
+    assert_eq!(
+        todo.render(&Brackets),
+        "[Priority (A)] [Creation 2024-01-01] [Description Call] [Project +mom] \
+         [Context @phone] [Deadline due:2024-01-02]"
+    );
+    assert_eq!(todo.render(&Plain), todo.to_string());
+
+    let mut col = TodoColumn::new("Calls");
+    col.add(Todo::from_str("Mom id:1").unwrap());
+    col.add(Todo::from_str("Dad parent:1").unwrap());
+    assert_eq!(
+        col.render(&Brackets),
+        "Calls: 0[Description Mom] [Metadata id:1], 1[Description Dad] [Metadata parent:1]"
+    );
+    assert_eq!(col.render(&Plain), col.to_string());
+
+    let style = StyleScheme::new().faded(Some("F")).project(Some("P"));
+    assert_eq!(
+        todo.render(&Ansi::new(style)),
+        format!("(A) 2024-01-01 Call P+mom{RESET} @phone due:2024-01-02")
+    );
+
+    let done = Todo::from_str("x Call +mom").unwrap();
+    assert_eq!(done.colored(style), format!("Fx Call +mom{RESET}"));
+}
+
+#[test]
+fn theme_loading() {
+    use crate::colors::{StyleScheme, RESET};
+    use crate::error::ThemeError;
+    use crate::theme::{Color, Style, Theme, THEMES};
+
+    for name in THEMES {
+        assert!(Theme::named(name).is_some(), "missing theme {name}");
+    }
+    assert!(Theme::named("nope").is_none());
+    assert_eq!(Theme::plain().scheme(), StyleScheme::default());
+
+    let default = Theme::default();
+    assert_eq!(default.scheme().deadline, "\x1b[1;38;5;1m");
+    assert_eq!(default.scheme().faded, "\x1b[2;38;5;7m");
+
+    assert_eq!(
+        "italic bright-cyan on #002B36".parse(),
+        Ok(Style::new()
+            .italic()
+            .fg(Color::Basic(14))
+            .bg(Color::Rgb(0, 0x2b, 0x36)))
+    );
+    assert_eq!(Style::new().fg(Color::Basic(9)).code(), "\x1b[91m");
+    assert!("bold #12345".parse::<Style>().is_err());
+    assert!("blue on".parse::<Style>().is_err());
+
+    let config = "\
+# todo.txt-cli config
+export TODO_DIR=\"$HOME/todo\"
+export ORANGE='\\\\033[38;5;208m'
+export PRI_A=$ORANGE
+export PRI_X=$WHITE
+export COLOR_DONE=$LIGHT_GREY
+export TODOTXT_COLOR_PROJECT='\\\\033[1;4;32m'
+export COLOR_DATE=${BLUE}
+";
+    let theme = Theme::parse(config).unwrap();
+    assert_eq!(theme.priority, Style::new().fg(Color::Palette(208)));
+    assert_eq!(theme.faded, Style::new().fg(Color::WHITE));
+    assert_eq!(
+        theme.project,
+        Style::new().bold().underline().fg(Color::GREEN)
+    );
+    assert_eq!(theme.creation, Style::new().fg(Color::BLUE));
+    assert_eq!(theme.completion, Style::new().fg(Color::BLUE));
+    assert_eq!(theme.context, Style::new());
+
+    let todo = Todo::from_str("(A) Paint +house").unwrap();
+    assert_eq!(
+        todo.colored(theme.scheme()),
+        format!("\x1b[38;5;208m(A){RESET} Paint \x1b[1;4;32m+house{RESET}")
+    );
+
+    let theme = Theme::parse("theme = solarized\ncontext = none").unwrap();
+    assert_eq!(theme.context, Style::new());
+    assert_eq!(theme.deadline, Theme::named("solarized").unwrap().deadline);
+
+    assert_eq!(Theme::parse("theme dark"), Err(ThemeError::BadLine(1)));
+    assert_eq!(
+        Theme::parse("\ntheme = neon"),
+        Err(ThemeError::UnknownTheme {
+            line: 2,
+            name: "neon".to_string()
+        })
+    );
+    assert_eq!(
+        Theme::parse("project = sparkly"),
+        Err(ThemeError::BadStyle {
+            line: 1,
+            value: "sparkly".to_string()
+        })
+    );
+    assert_eq!(
+        Theme::parse("COLOR_META=$NEON"),
+        Err(ThemeError::UnknownVariable {
+            line: 1,
+            name: "NEON".to_string()
+        })
+    );
+}
+
+#[test]
+fn board_layout() {
+    use crate::board::{Board, Overflow};
+    use crate::colors::{StyleScheme, FADE, RESET};
+
+    let mut table = TodoTable::new(Some("Board"));
+    table.add_col("中文");
+    table.add_col("Done");
+    table.add_col("Empty");
+    table.add_todo(Todo::from_str("买牛奶和面包 id:1").unwrap(), "中文");
+    table.add_todo(Todo::from_str("Sub parent:1").unwrap(), "中文");
+    table.add_todo(Todo::from_str("x Old thing").unwrap(), "Done");
+
+    let board = Board::new(StyleScheme::default()).width(36);
+    let lines = [
+        "=== Board ===",
+        "中文       | Done       | Empty",
+        "-----------+------------+-----------",
+        "买牛奶和面 | x Old      |",
+        "  包 id:1  |   thing    |",
+        "  Sub      |            |",
+        "    parent |            |",
+        "    :1     |            |",
+    ];
+    assert_eq!(table.render(&board), lines.join("\n") + "\n");
+
+    let board = board.overflow(Overflow::Truncate);
+    assert!(table.render(&board).contains("买牛奶和…  | x Old thi… |\n"));
+
+    // Styles carry over to wrapped lines, and are reset at the end of each.
+    let style = StyleScheme::new().faded(Some(FADE));
+    let board = Board::new(style).width(10);
+    let mut done = TodoTable::new(Some("Done"));
+    done.add_col("Done");
+    done.add_todo(Todo::from_str("x Old thing").unwrap(), "Done");
+    assert_eq!(
+        done.render(&board),
+        format!("=== Done ===\nDone\n----------\n{FADE}x Old{RESET}\n  {FADE}thing{RESET}\n")
+    );
+
+    // Deep subtasks stay within narrow columns.
+    let mut deep = TodoTable::new(Some("Deep"));
+    deep.add_col("Todo");
+    for line in [
+        "Top id:1",
+        "Mid id:2 parent:1",
+        "Low id:3 parent:2",
+        "Leaf parent:3",
+    ] {
+        deep.add_todo(Todo::from_str(line).unwrap(), "Todo");
+    }
+    for width in [5, 10] {
+        for overflow in [Overflow::Wrap, Overflow::Truncate] {
+            let board = Board::new(StyleScheme::default())
+                .width(width)
+                .overflow(overflow);
+            let rendered = deep.render(&board);
+            assert!(rendered
+                .lines()
+                .skip(1)
+                .all(|line| line.chars().count() <= width));
+        }
+    }
+
+    // Too narrow to fit anything, but still laid out.
+    assert!(table
+        .render(&Board::new(style).width(0))
+        .starts_with("=== Board ===\n… | … | …\n"));
+
+    assert_eq!(
+        done.to_string(),
+        "=== Done ===\n| Done |\n| x Old thing\n\n"
+    );
+}
+
+#[test]
+fn color_modes() {
+    use crate::board::Board;
+    use crate::colors::{auto_enabled, ColorMode, DEFAULT_STYLE};
+    use crate::error::ThemeError;
+    use crate::theme::Theme;
+
+    assert!(auto_enabled(None, None, true));
+    assert!(!auto_enabled(None, None, false));
+    assert!(!auto_enabled(Some("1"), None, true));
+    assert!(auto_enabled(Some(""), None, true));
+    assert!(auto_enabled(None, Some("1"), false));
+    assert!(auto_enabled(Some("1"), Some("1"), false));
+    assert!(!auto_enabled(None, Some("0"), false));
+
+    // Colors stay on unless turned off.
+    let todo = Todo::from_str("(A) Call mom +family").unwrap();
+    assert_eq!(ColorMode::default(), ColorMode::Always);
+    assert_ne!(todo.colored(DEFAULT_STYLE), todo.to_string());
+    assert_eq!(DEFAULT_STYLE.with_color(ColorMode::Always), DEFAULT_STYLE);
+    let never = DEFAULT_STYLE.with_color(ColorMode::Never);
+    assert_eq!(never, StyleScheme::new());
+    assert_eq!(todo.colored(never), todo.to_string());
+
+    let mut table = TodoTable::new(Some("Family"));
+    table.add_col("Calls");
+    table.add_todo(todo, "Calls");
+    assert_eq!(table.colored(never), table.to_string());
+    assert_eq!(
+        table.columns()[0].colored(never),
+        table.columns()[0].to_string()
+    );
+    assert!(!table.render(&Board::new(never)).contains('\x1b'));
+    assert!(table.render(&Board::new(DEFAULT_STYLE)).contains('\x1b'));
+
+    assert_eq!(Theme::default().color, ColorMode::Always);
+    let theme = Theme::parse("color = never\ntheme = dark").unwrap();
+    assert_eq!(theme.color, ColorMode::Never);
+    assert_eq!(theme.scheme(), StyleScheme::new());
+    assert_eq!(
+        Theme::parse("color = sometimes"),
+        Err(ThemeError::BadColorMode {
+            line: 1,
+            value: "sometimes".to_string()
+        })
+    );
+}
+
+#[test]
+fn relative_dates() {
+    use crate::query::Query;
+    use crate::relative::Period;
+
+    let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let resolve = |s: &str, today| s.parse::<RelativeDate>().unwrap().resolve(today);
+
+    // Offsets cross year boundaries, which `Nd` used to get wrong.
+    assert_eq!(resolve("1d", day(2024, 12, 31)), Some(day(2025, 1, 1)));
+    assert_eq!(resolve("+60d", day(2023, 12, 1)), Some(day(2024, 1, 30)));
+    assert_eq!(resolve("-1y", day(2024, 2, 29)), Some(day(2023, 2, 28)));
+    assert_eq!(resolve("-2b", day(2024, 1, 8)), Some(day(2024, 1, 4)));
+    assert_eq!(resolve("-6b", day(2024, 1, 7)), Some(day(2023, 12, 29)));
+    assert_eq!(resolve("-4000000000b", day(2024, 1, 8)), None);
+    assert!(Todo::from_str("Long ago due:-4000000000b").is_err());
+    assert_eq!(resolve("today-1w", day(2024, 1, 8)), Some(day(2024, 1, 1)));
+    assert_eq!(
+        resolve("Yesterday", day(2024, 3, 1)),
+        Some(day(2024, 2, 29))
+    );
+
+    // 2024-01-07 is a Sunday.
+    let sunday = day(2024, 1, 7);
+    assert_eq!(resolve("sun", sunday), Some(day(2024, 1, 14)));
+    assert_eq!(resolve("Monday", sunday), Some(day(2024, 1, 8)));
+    assert_eq!(resolve("next-mon", sunday), Some(day(2024, 1, 8)));
+    assert_eq!(resolve("next-sun", sunday), Some(day(2024, 1, 14)));
+    assert_eq!(resolve("eow", sunday), Some(sunday));
+    assert_eq!(resolve("next-week", sunday), Some(day(2024, 1, 8)));
+    assert_eq!(resolve("eom", day(2024, 2, 10)), Some(day(2024, 2, 29)));
+    assert_eq!(resolve("eoy", sunday), Some(day(2024, 12, 31)));
+    assert_eq!(
+        resolve("next-month", day(2024, 12, 31)),
+        Some(day(2025, 1, 1))
+    );
+    assert_eq!(resolve("next-year", sunday), Some(day(2025, 1, 1)));
+
+    for text in ["soon", "today3d", "++2w", "next-someday", "2024-13-01"] {
+        assert!(
+            text.parse::<RelativeDate>().is_err(),
+            "{text} should not parse"
+        );
+    }
+
+    for text in [
+        "today",
+        "tomorrow",
+        "-3b",
+        "+2w",
+        "fri",
+        "next-tue",
+        "eom",
+        "next-year",
+    ] {
+        assert_eq!(
+            text.parse::<RelativeDate>().unwrap().to_string(),
+            text,
+            "{text} should round-trip"
+        );
+    }
+    assert_eq!(
+        "EOW".parse::<RelativeDate>().ok(),
+        Some(RelativeDate::EndOf(Period::Week))
+    );
+
+    // Relative dates count from the creation date, or else from now.
+    let now = day(2024, 1, 7).and_hms_opt(18, 30, 0).unwrap();
+    let todo = Todo::from_str_at("Plan week due:tom t:fri", now).unwrap();
+    assert_eq!(todo.to_string(), "Plan week due:2024-01-08 t:2024-01-12");
+    let todo = Todo::from_str_at("2024-02-01 Plan due:eom", now).unwrap();
+    assert_eq!(
+        todo.deadline,
+        TodoDate::Day(day(2024, 2, 29).and_time(NaiveTime::MIN))
+    );
+
+    let todo = Todo::parse_lossless("2024-01-07 Plan week due:next-mon").unwrap();
+    assert_eq!(todo.to_string(), "2024-01-07 Plan week due:next-mon");
+    assert_eq!(
+        todo.deadline,
+        TodoDate::Day(day(2024, 1, 8).and_time(NaiveTime::MIN))
+    );
+
+    // Lossless parsing keeps relative tokens even without a creation date,
+    // until they're pinned.
+    let text = "Call  mom due:tomorrow t:today\n";
+    let mut list = TodoList::parse(text);
+    assert_eq!(list.to_string(), text);
+    let today = Local::now().date_naive();
+    let todo = list.iter_mut().next().unwrap();
+    assert_eq!(
+        todo.deadline,
+        TodoDate::Day(today.succ_opt().unwrap().and_time(NaiveTime::MIN))
+    );
+    todo.pin_relative_dates();
+    let pinned = format!("Call  mom due:{} t:{today}", today.succ_opt().unwrap());
+    assert_eq!(todo.to_string(), pinned);
+    let later = (today + chrono::Duration::days(30)).and_time(NaiveTime::MIN);
+    let reread = Todo::from_str_at(&pinned, later).unwrap();
+    assert_eq!(
+        (&reread.deadline, reread.threshold),
+        (&todo.deadline, todo.threshold)
+    );
+
+    let todo = Todo::from_str("Plan due:soon").unwrap();
+    assert_eq!(todo.deadline, TodoDate::Never);
+    assert_eq!(todo.get_meta("due"), Some(&"soon".to_string()));
+    assert!(Todo::from_str("Plan due:xd").is_err());
+
+    let mut col = TodoColumn::new("Todo");
+    col.add(Todo::from_str_at("Soon due:tomorrow", Local::now().naive_local()).unwrap());
+    col.add(Todo::from_str_at("Later due:+1y", Local::now().naive_local()).unwrap());
+    let found = col.query(&Query::parse("due<=next-month").unwrap());
+    assert_eq!(found.len(), 1);
+    assert!(Query::parse("due<soon").is_err());
+}
+
+#[test]
+/// Tests the `_at` variants of time-dependent APIs with a fixed clock.
+fn clock() {
+    use crate::archive::ArchiveOptions;
+    use crate::clock::FixedClock;
+    use crate::query::Query;
+    use chrono::Duration;
+
+    let start = NaiveDate::from_ymd_opt(2024, 5, 6)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut clock = FixedClock::new(start);
+
+    let deadline = TodoDate::Day(start + Duration::days(2));
+    let mut todo = Todo::new_at("Renew passport", deadline, TodoPriority::A, clock.now());
+    assert_eq!(todo.creation, Some(start));
+    assert!(!todo.due_at(clock.now()));
+
+    let mut table = TodoTable::new(Some("Errands"));
+    table.add_col("Now");
+    table.add_todo(todo.clone(), "Now");
+    assert!(!table.due_at(clock.now()));
+    assert!(!table.col("Now").unwrap().due_at(clock.now()));
+
+    clock.advance(Duration::days(2));
+    assert!(todo.due_at(clock.now()));
+    assert!(table.due_at(clock.now()));
+    assert!(table.col("Now").unwrap().due_at(clock.now()));
+
+    todo.set_creation_at(clock.now());
+    assert_eq!(todo.creation, Some(clock.now()));
+
+    // Relative dates resolve against the clock rather than the system.
+    let hidden = Todo::from_str_at("Water plants t:tomorrow rec:1w", clock.now()).unwrap();
+    assert!(hidden.is_hidden_at(clock.now()));
+    assert!(!hidden.is_actionable_at(clock.now()));
+    assert!(hidden.is_actionable_at(clock.now() + Duration::days(1)));
+
+    table.add_todo(hidden.clone(), "Now");
+    assert_eq!(table.iter_visible_at(clock.now()).count(), 1);
+    assert_eq!(table.iter_actionable_at(clock.now()).count(), 1);
+    let tomorrow = clock.now() + Duration::days(1);
+    assert_eq!(
+        table.col("Now").unwrap().iter_visible_at(tomorrow).count(),
+        2
+    );
+    assert_eq!(
+        table
+            .col("Now")
+            .unwrap()
+            .iter_actionable_at(tomorrow)
+            .count(),
+        2
+    );
+
+    let mut recurring = hidden;
+    let next = recurring.complete_at(clock.now()).unwrap();
+    assert_eq!(recurring.completion_date, Some(clock.now()));
+    assert_eq!(next.threshold.unwrap().date().to_string(), "2024-05-15");
+    assert!(!recurring.due_at(clock.now()));
+
+    // The completed todo is kept for a day, then archived.
+    let options = ArchiveOptions {
+        keep_days: Some(1),
+        ..Default::default()
+    };
+    let mut list = TodoList::new();
+    list.add(recurring);
+    let mut done = TodoList::new();
+    assert_eq!(list.archive_at(&mut done, options, clock.now()), 0);
+    clock.set(clock.now() + Duration::days(3));
+    assert_eq!(list.archive_at(&mut done, options, clock.now()), 1);
+    assert_eq!(done.iter().count(), 1);
+
+    let query: Query = "due".parse().unwrap();
+    let todos = [todo];
+    assert!(query.filter_at(&todos, start).is_empty());
+    assert_eq!(query.filter_at(&todos, start + Duration::days(2)).len(), 1);
+
+    // Implementors only need due_at.
+    struct Overdue;
+    impl IsDue for Overdue {
+        fn due_at(&self, now: NaiveDateTime) -> bool {
+            now.date() > NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        }
+    }
+    assert!(Overdue.due() && Overdue.due_with(start, DuePolicy::EndOfDay));
+}
+
+#[test]
+/// Tests deadlines with times and UTC offsets, and the due policy.
+fn timed_deadlines() {
+    use chrono::{Duration, FixedOffset, Utc};
+
+    let at = |s: &str| NaiveDateTime::parse_from_str(s, "%F %H:%M").unwrap();
+
+    let todo = Todo::from_str("Send report due:2024-05-01T14:30").unwrap();
+    assert_eq!(todo.deadline, TodoDate::Timed(at("2024-05-01 14:30")));
+    assert_eq!(todo.to_string(), "Send report due:2024-05-01T14:30");
+    assert!(!todo.due_at(at("2024-05-01 14:29")));
+    assert!(todo.due_at(at("2024-05-01 14:30")));
+
+    // Midnight is a time like any other, rather than the whole day.
+    let midnight = Todo::from_str("Launch due:2024-05-01T00:00").unwrap();
+    assert_eq!(midnight.deadline, TodoDate::Timed(at("2024-05-01 00:00")));
+    assert_eq!(midnight.to_string(), "Launch due:2024-05-01T00:00");
+    assert!(midnight.due_with(at("2024-05-01 00:00"), DuePolicy::EndOfDay));
+
+    // Offsets are written in one form, and seconds only when there are any.
+    for (text, written) in [
+        ("due:2024-05-01T14:30+02:00", "due:2024-05-01T14:30+02:00"),
+        ("due:2024-05-01T14:30+0200", "due:2024-05-01T14:30+02:00"),
+        (
+            "due:2024-05-01T09:30:15-05",
+            "due:2024-05-01T09:30:15-05:00",
+        ),
+        ("due:2024-05-01T12:30z", "due:2024-05-01T12:30Z"),
+    ] {
+        let mut todo = Todo::from_str(text).unwrap();
+        assert!(matches!(todo.deadline, TodoDate::Zoned(_)), "{text}");
+        todo.description = TodoDescription::default();
+        assert_eq!(todo.to_string(), written);
+    }
+
+    // Malformed times aren't deadlines.
+    let todo = Todo::from_str("Call due:2024-05-01T25:00").unwrap();
+    assert!(todo.deadline.is_none());
+    assert_eq!(todo.to_string(), "Call due:2024-05-01T25:00");
+
+    // The same moment, from three timezones.
+    let berlin = Todo::from_str("Review due:2024-05-01T14:30+02:00").unwrap();
+    let new_york = FixedOffset::west_opt(4 * 3600).unwrap();
+    let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+    let policy = DuePolicy::default();
+    let due = berlin.deadline.due_in(&Utc, policy).unwrap();
+    assert_eq!(due.naive_utc(), at("2024-05-01 12:30"));
+    assert_eq!(
+        berlin
+            .deadline
+            .due_in(&new_york, policy)
+            .unwrap()
+            .naive_local(),
+        at("2024-05-01 08:30")
+    );
+    assert_eq!(
+        berlin
+            .deadline
+            .due_in(&tokyo, policy)
+            .unwrap()
+            .naive_local(),
+        at("2024-05-01 21:30")
+    );
+
+    // Dates and times without an offset are read in the given timezone.
+    let floating = Todo::from_str("Water plants due:2024-05-01").unwrap();
+    let due = floating.deadline.due_in(&tokyo, policy).unwrap();
+    assert_eq!(due.naive_utc(), at("2024-04-30 15:00"));
+
+    // Date-only deadlines fall due at the start or end of the day.
+    let noon = at("2024-05-01 12:00");
+    assert!(floating.due_with(noon, DuePolicy::StartOfDay));
+    assert!(!floating.due_with(noon, DuePolicy::EndOfDay));
+    assert!(floating.due_with(noon + Duration::hours(12), DuePolicy::EndOfDay));
+    // Times apply as they are.
+    let timed = TodoDate::Timed(noon + Duration::hours(1));
+    assert!(timed.due_with(noon + Duration::hours(1), DuePolicy::EndOfDay));
+    let end = floating.deadline.due_in(&Utc, DuePolicy::EndOfDay).unwrap();
+    assert_eq!(end.naive_utc(), at("2024-05-02 00:00"));
+
+    let mut table = TodoTable::new(Some("Garden"));
+    table.add_col("Now");
+    table.add_todo(floating, "Now");
+    assert!(!table.due_with(noon, DuePolicy::EndOfDay));
+
+    // Recurring deadlines keep their time and offset.
+    let mut standup = Todo::from_str("Standup due:2024-05-01T09:00+02:00 rec:1d").unwrap();
+    let next = standup.complete_at(at("2024-05-03 10:00")).unwrap();
+    assert_eq!(next.deadline.to_string(), "due:2024-05-04T09:00+02:00");
+
+    // So do CSV exports.
+    let mut sheet = TodoTable::new(Some("Sheet"));
+    sheet.add_col("Todos");
+    sheet.add_todo(standup, "Todos");
+    sheet.add_todo(midnight, "Todos");
+    let csv = crate::csv::table_to_csv(&sheet, ',');
+    assert!(csv.contains(",2024-05-01T09:00+02:00,"));
+    let parsed = crate::csv::parse_csv(&csv, ',').unwrap();
+    let deadlines: Vec<_> = parsed.iter().map(|t| t.deadline.clone()).collect();
+    let expected: Vec<_> = sheet.iter().map(|t| t.deadline.clone()).collect();
+    assert_eq!(deadlines, expected);
+}
@@ -0,0 +1,570 @@
+//! Color themes, producing `StyleScheme`s without writing escape codes.
+//!
+//! A `Style` is a foreground and background `Color` plus attributes. Colors
+//! are one of the 16 basic terminal colors, one of the 256 palette colors,
+//! or 24-bit RGB. A `Theme` has a style for each field of `StyleScheme`.
+//!
+//! Built-in themes are loaded with `Theme::named`; see `THEMES` for their
+//! names. Themes can also be loaded from config files with a `key = style`
+//! per line, where the keys are the fields of `StyleScheme`, `theme` starts
+//! from a built-in theme, and `color` is `always`, `never` or `auto`:
+//!
+//! ```text
+//! # Start from the dark theme, with loud deadlines.
+//! theme = dark
+//! color = always
+//! deadline = bold underline #ff0000 on black
+//! project = italic 214
+//! ```
+//!
+//! A style is made of words, in any order:
+//!
+//! - `bold`, `italic`, `underline` and `faded` (or `dim`)
+//! - a foreground color: a name such as `red` or `bright-blue`, a palette
+//!   index from `0` to `255`, or `#rrggbb`
+//! - `on` followed by a background color
+//! - `none`, for no styling
+//!
+//! The color variables of todo.txt-cli's `todo.cfg` are understood too,
+//! optionally prefixed with `TODOTXT_`: `COLOR_DONE`, `PRI_A` (or `PRI_X`),
+//! `COLOR_PROJECT`, `COLOR_CONTEXT`, `COLOR_DATE` and `COLOR_META`. Their
+//! values may be escape codes like `'\\033[1;31m'` or other variables like
+//! `$LIGHT_RED`, including the predefined `$RED` to `$WHITE`. Other keys,
+//! including shell lines like `export TODO_DIR=...`, are kept as variables.
+//!
+//! Example:
+//! ```
+//! use todo_lib::prelude::*;
+//! use todo_lib::theme::{Color, Style, Theme};
+//!
+//! let theme = Theme::parse("color = always\nproject = bold #ff8800").unwrap();
+//! assert_eq!(theme.project, Style::new().bold().fg(Color::Rgb(255, 136, 0)));
+//! assert_eq!(theme.scheme().project, "\x1b[1;38;2;255;136;0m");
+//!
+//! let todo = Todo::from_str("Paint +house").unwrap();
+//! assert_eq!(todo.colored(theme.scheme()), "Paint \x1b[1;38;2;255;136;0m+house\x1b[0m");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::colors::{ColorMode, StyleScheme};
+use crate::error::ThemeError;
+
+/// The names of the built-in themes.
+pub const THEMES: [&str; 5] = ["default", "plain", "light", "dark", "solarized"];
+
+/// The escape codes of todo.txt-cli's predefined color variables.
+const TODOTXT_COLORS: [(&str, &str); 17] = [
+    ("BLACK", "0;30"),
+    ("RED", "0;31"),
+    ("GREEN", "0;32"),
+    ("BROWN", "0;33"),
+    ("BLUE", "0;34"),
+    ("PURPLE", "0;35"),
+    ("CYAN", "0;36"),
+    ("LIGHT_GREY", "0;37"),
+    ("DARK_GREY", "1;30"),
+    ("LIGHT_RED", "1;31"),
+    ("LIGHT_GREEN", "1;32"),
+    ("YELLOW", "1;33"),
+    ("LIGHT_BLUE", "1;34"),
+    ("LIGHT_PURPLE", "1;35"),
+    ("LIGHT_CYAN", "1;36"),
+    ("WHITE", "1;37"),
+    ("DEFAULT", "0"),
+];
+
+/// A terminal color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// One of the 16 basic colors: 0 to 7 are black, red, green, yellow,
+    /// blue, magenta, cyan and white, and 8 to 15 their bright versions.
+    Basic(u8),
+    /// A color of the 256-color palette.
+    Palette(u8),
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    pub const BLACK: Self = Self::Basic(0);
+    pub const RED: Self = Self::Basic(1);
+    pub const GREEN: Self = Self::Basic(2);
+    pub const YELLOW: Self = Self::Basic(3);
+    pub const BLUE: Self = Self::Basic(4);
+    pub const MAGENTA: Self = Self::Basic(5);
+    pub const CYAN: Self = Self::Basic(6);
+    pub const WHITE: Self = Self::Basic(7);
+
+    /// Returns the SGR parameters for the color, as a foreground or
+    /// background.
+    fn params(&self, background: bool) -> String {
+        let offset = if background { 10 } else { 0 };
+        match *self {
+            Self::Basic(n) if n < 8 => (30 + offset + n as u16).to_string(),
+            Self::Basic(n) => (90 + offset + (n as u16 & 7)).to_string(),
+            Self::Palette(n) => format!("{};5;{n}", 38 + offset),
+            Self::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", 38 + offset),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ();
+
+    /// Parses a color name, a palette index or `#rrggbb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(());
+            }
+
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ());
+            return Ok(Self::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+
+        if let Ok(n) = s.parse() {
+            return Ok(Self::Palette(n));
+        }
+
+        let s = s.to_lowercase();
+        let (bright, name) = match s
+            .strip_prefix("bright-")
+            .or_else(|| s.strip_prefix("light-"))
+        {
+            Some(name) => (8, name),
+            None => (0, s.as_str()),
+        };
+
+        let n = match name {
+            "black" => 0,
+            "red" => 1,
+            "green" => 2,
+            "yellow" => 3,
+            "blue" => 4,
+            "magenta" | "purple" => 5,
+            "cyan" => 6,
+            "white" | "gray" | "grey" => 7,
+            "dark-gray" | "dark-grey" if bright == 0 => return Ok(Self::Basic(8)),
+            _ => return Err(()),
+        };
+
+        Ok(Self::Basic(bright + n))
+    }
+}
+
+/// Colors and attributes for a piece of text.
+///
+/// Built with chained methods, starting from `Style::new()`:
+/// ```
+/// use todo_lib::theme::{Color, Style};
+///
+/// let style = Style::new().bold().fg(Color::RED).bg(Color::Palette(236));
+/// assert_eq!(style.code(), "\x1b[1;31;48;5;236m");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    faded: bool,
+    italic: bool,
+    underline: bool,
+    code: String,
+}
+
+impl Style {
+    /// Returns a style without colors or attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self.update()
+    }
+
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self.update()
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self.update()
+    }
+
+    pub fn faded(mut self) -> Self {
+        self.faded = true;
+        self.update()
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self.update()
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self.update()
+    }
+
+    /// Returns the escape code which sets the style, or an empty string if
+    /// the style is empty.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Parses an escape code, such as `\033[1;31m` or `\x1b[1;31m`, or just
+    /// its parameters.
+    ///
+    /// Parameters which reset or don't set a style, such as blinking, are
+    /// ignored.
+    pub fn from_escape(s: &str) -> Option<Self> {
+        // todo.cfg doubles the backslash, since `echo -e` unescapes it.
+        let escape = s.trim_start_matches('\\');
+        let params = ["033[", "e[", "x1b[", "\x1b["]
+            .iter()
+            .find_map(|prefix| escape.strip_prefix(prefix))
+            .unwrap_or(s);
+        let params = params.strip_suffix('m').unwrap_or(params);
+
+        let mut style = Self::new();
+        let mut params = params
+            .split(';')
+            .map(|p| if p.is_empty() { Ok(0) } else { p.parse::<u8>() });
+
+        while let Some(param) = params.next() {
+            style = match param.ok()? {
+                1 => style.bold(),
+                2 => style.faded(),
+                3 => style.italic(),
+                4 => style.underline(),
+                n @ 30..=37 => style.fg(Color::Basic(n - 30)),
+                n @ 40..=47 => style.bg(Color::Basic(n - 40)),
+                n @ 90..=97 => style.fg(Color::Basic(n - 90 + 8)),
+                n @ 100..=107 => style.bg(Color::Basic(n - 100 + 8)),
+                n @ (38 | 48) => {
+                    let color = match params.next()?.ok()? {
+                        5 => Color::Palette(params.next()?.ok()?),
+                        2 => Color::Rgb(
+                            params.next()?.ok()?,
+                            params.next()?.ok()?,
+                            params.next()?.ok()?,
+                        ),
+                        _ => return None,
+                    };
+
+                    if n == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    }
+                }
+                _ => style,
+            };
+        }
+
+        Some(style)
+    }
+
+    /// Recomputes the escape code.
+    fn update(mut self) -> Self {
+        let mut params = Vec::new();
+        for (set, param) in [
+            (self.bold, "1"),
+            (self.faded, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+        ] {
+            if set {
+                params.push(param.to_string());
+            }
+        }
+        params.extend(self.fg.map(|color| color.params(false)));
+        params.extend(self.bg.map(|color| color.params(true)));
+
+        self.code = if params.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", params.join(";"))
+        };
+        self
+    }
+}
+
+impl FromStr for Style {
+    type Err = ();
+
+    /// Parses words such as `bold red on #002b36`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Self::new();
+        let mut words = s.split_whitespace();
+
+        while let Some(word) = words.next() {
+            style = match word.to_lowercase().as_str() {
+                "none" => style,
+                "bold" => style.bold(),
+                "faded" | "dim" => style.faded(),
+                "italic" => style.italic(),
+                "underline" => style.underline(),
+                "on" => style.bg(words.next().ok_or(())?.parse()?),
+                _ => style.fg(word.parse()?),
+            };
+        }
+
+        Ok(style)
+    }
+}
+
+impl Display for Style {
+    /// Writes the escape code which sets the style.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+/// A style for each field of `StyleScheme`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub color: ColorMode,
+
+    pub faded: Style,
+
+    pub tick: Style,
+    pub priority: Style,
+    pub completion: Style,
+    pub creation: Style,
+
+    pub description: Style,
+    pub context: Style,
+    pub project: Style,
+
+    pub deadline: Style,
+    pub metadata: Style,
+}
+
+impl Default for Theme {
+    /// Returns the `default` theme, which looks like `DEFAULT_STYLE`.
+    fn default() -> Self {
+        Self::named("default").expect("the default theme is built in")
+    }
+}
+
+impl Theme {
+    /// Returns a theme without any styling.
+    pub fn plain() -> Self {
+        Self {
+            color: ColorMode::Always,
+            faded: Style::new(),
+            tick: Style::new(),
+            priority: Style::new(),
+            completion: Style::new(),
+            creation: Style::new(),
+            description: Style::new(),
+            context: Style::new(),
+            project: Style::new(),
+            deadline: Style::new(),
+            metadata: Style::new(),
+        }
+    }
+
+    /// Returns a built-in theme by name; see `THEMES`.
+    pub fn named<S: AsRef<str>>(name: S) -> Option<Self> {
+        // Faded, priority, completion, creation, context, project, deadline
+        // and metadata colors; the attributes are the same for every theme.
+        let colors = match name.as_ref().to_lowercase().as_str() {
+            "plain" => return Some(Self::plain()),
+            "default" => [7, 6, 5, 3, 2, 3, 1, 4].map(Color::Palette),
+            "light" => [247, 25, 90, 94, 28, 130, 160, 61].map(Color::Palette),
+            "dark" => [244, 81, 176, 180, 114, 221, 203, 110].map(Color::Palette),
+            "solarized" => [
+                (0x58, 0x6e, 0x75),
+                (0x26, 0x8b, 0xd2),
+                (0xd3, 0x36, 0x82),
+                (0xb5, 0x89, 0x00),
+                (0x85, 0x99, 0x00),
+                (0xcb, 0x4b, 0x16),
+                (0xdc, 0x32, 0x2f),
+                (0x6c, 0x71, 0xc4),
+            ]
+            .map(|(r, g, b)| Color::Rgb(r, g, b)),
+            _ => return None,
+        };
+
+        let [faded, priority, completion, creation, context, project, deadline, metadata] = colors;
+        Some(Self {
+            color: ColorMode::Always,
+            faded: Style::new().faded().fg(faded),
+            tick: Style::new(),
+            priority: Style::new().bold().fg(priority),
+            completion: Style::new().underline().fg(completion),
+            creation: Style::new().underline().fg(creation),
+            description: Style::new(),
+            context: Style::new().italic().fg(context),
+            project: Style::new().italic().fg(project),
+            deadline: Style::new().bold().fg(deadline),
+            metadata: Style::new().italic().fg(metadata),
+        })
+    }
+
+    /// Parses a theme config file; see the module docs for the format.
+    ///
+    /// Unset fields have no styling, unless a `theme` line starts from a
+    /// built-in theme.
+    pub fn parse<S: AsRef<str>>(text: S) -> Result<Self, ThemeError> {
+        let mut theme = Self::plain();
+        let mut variables: HashMap<String, Style> = TODOTXT_COLORS
+            .iter()
+            .filter_map(|(name, params)| Some((name.to_string(), Style::from_escape(params)?)))
+            .collect();
+        let mut has_pri_a = false;
+
+        for (i, line) in text.as_ref().lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(ThemeError::BadLine(line_no))?;
+            let key = key.trim();
+            let key = key.strip_prefix("export ").unwrap_or(key).trim();
+            let key = key.strip_prefix("TODOTXT_").unwrap_or(key);
+            let value = unquote(value.trim());
+
+            if key == "theme" {
+                let color = theme.color;
+                theme = Self::named(value).ok_or_else(|| ThemeError::UnknownTheme {
+                    line: line_no,
+                    name: value.to_string(),
+                })?;
+                theme.color = color;
+                continue;
+            }
+
+            if key == "color" {
+                theme.color = match value.to_lowercase().as_str() {
+                    "always" => ColorMode::Always,
+                    "never" => ColorMode::Never,
+                    "auto" => ColorMode::Auto,
+                    _ => {
+                        return Err(ThemeError::BadColorMode {
+                            line: line_no,
+                            value: value.to_string(),
+                        })
+                    }
+                };
+                continue;
+            }
+
+            let variable = value
+                .strip_prefix('$')
+                .map(|name| name.trim_start_matches('{').trim_end_matches('}'));
+            let style = match variable {
+                Some(name) => variables.get(name).cloned(),
+                None if value.contains('[') => Style::from_escape(value),
+                None => value.parse().ok(),
+            };
+
+            // `PRI_X` styles priorities without their own color, so it only
+            // applies if `PRI_A` doesn't.
+            match key {
+                "PRI_A" => has_pri_a = true,
+                "PRI_X" if has_pri_a => continue,
+                _ => {}
+            }
+
+            let fields = theme.fields_mut(key);
+            if fields.is_empty() {
+                // Config files set other variables too, such as paths.
+                if let Some(style) = style {
+                    variables.insert(key.to_string(), style);
+                }
+                continue;
+            }
+
+            let style = style.ok_or_else(|| match variable {
+                Some(name) => ThemeError::UnknownVariable {
+                    line: line_no,
+                    name: name.to_string(),
+                },
+                None => ThemeError::BadStyle {
+                    line: line_no,
+                    value: value.to_string(),
+                },
+            })?;
+            for field in fields {
+                *field = style.clone();
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Reads a theme config file.
+    ///
+    /// Parse errors are returned as `io::ErrorKind::InvalidData`.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Self::parse(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a theme config file from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(File::open(path)?)
+    }
+
+    /// Returns the theme as a `StyleScheme`, borrowing its escape codes, or
+    /// an empty scheme if its color mode turns colors off.
+    pub fn scheme(&self) -> StyleScheme<'_> {
+        StyleScheme {
+            faded: self.faded.code(),
+            tick: self.tick.code(),
+            priority: self.priority.code(),
+            completion: self.completion.code(),
+            creation: self.creation.code(),
+            description: self.description.code(),
+            context: self.context.code(),
+            project: self.project.code(),
+            deadline: self.deadline.code(),
+            metadata: self.metadata.code(),
+        }
+        .with_color(self.color)
+    }
+
+    /// Returns the fields set by a config key.
+    fn fields_mut(&mut self, key: &str) -> Vec<&mut Style> {
+        match key {
+            "faded" | "COLOR_DONE" => vec![&mut self.faded],
+            "tick" => vec![&mut self.tick],
+            "priority" | "PRI_A" | "PRI_X" => vec![&mut self.priority],
+            "completion" => vec![&mut self.completion],
+            "creation" => vec![&mut self.creation],
+            "COLOR_DATE" => vec![&mut self.completion, &mut self.creation],
+            "description" => vec![&mut self.description],
+            "context" | "COLOR_CONTEXT" => vec![&mut self.context],
+            "project" | "COLOR_PROJECT" => vec![&mut self.project],
+            "deadline" => vec![&mut self.deadline],
+            "metadata" | "COLOR_META" => vec![&mut self.metadata],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Strips matching single or double quotes.
+fn unquote(s: &str) -> &str {
+    for quote in ['\'', '"'] {
+        if let Some(s) = s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return s;
+        }
+    }
+
+    s
+}